@@ -1,120 +1,542 @@
 pub mod backtracking {
     use ethnum::u256;
-    use crate::pieces::piece::*;
+    use crate::pieceset::pieceset::{PieceSet, IntoPieceSet};
     use crate::board::board::*;
     use crate::solutionset::solutionset::*;
-    
-    type Bitmap = u256;
+    use crate::solver::solver::{SolveOptions, Pruning};
+    use std::collections::HashSet;
+    use std::ops::ControlFlow;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::thread;
+    use crate::validate::validate::validate_board;
+    use crate::error::error::SolverError;
+    use crate::symmetry::symmetry::*;
+    use crate::bitmap::bitmap::*;
 
+    struct Placement<B: Bitmap> {
+        cells: B,
+        // The open cells next to the piece, where dead-region pruning looks.
+        border: B,
+        // Index of the placement in the list the symmetries act on.
+        row: usize,
+    }
+
+    // The placement chosen for each piece so far, pointing into Solver::table.
+    // The copies of a piece kind are separate pieces.
     #[derive(Clone)]
-    struct Solution(Vec<Bitmap>);
+    struct Solution<'a, B: Bitmap>(Vec<Option<&'a Placement<B>>>);
 
-    impl Solution {
+    impl<B: Bitmap> Solution<'_, B> {
         fn new(num_pieces: usize) -> Self {
-            Solution(vec![Bitmap::from(0u8); num_pieces])
+            Solution(vec![None; num_pieces])
         }
     }
 
-    struct Solver {
+    /// What a backtracking search did. A search on several threads counts every
+    /// node once, the ones split off on the calling thread included, so a full
+    /// search gives the same figures on any number of threads. A search cut short
+    /// by a solution limit does not: the threads expand the top of the tree
+    /// breadth first and run on until they see the stop, so they visit nodes the
+    /// single-threaded search never reaches.
+    #[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+    pub struct SearchStats {
+        /// Number of partial solutions visited.
+        pub nodes: usize,
+        /// Number of placements rejected by dead-region pruning.
+        pub pruned: usize,
+    }
+
+    struct Solver<B: Bitmap> {
         height: usize,
         width: usize,
+        // Every region of open cells must be a multiple of region_unit (see
+        // PieceSet::region_unit) and no smaller than min_piece_size to be filled.
+        region_unit: usize,
+        min_piece_size: usize,
         num_pieces: usize,
+        // The kind of each piece, with the copies of a kind next to each other.
+        kind_of: Vec<usize>,
         num_solutions: usize,
-        table: Vec<Vec<Vec<Bitmap>>>,
+        num_threads: usize,
+        table: Vec<Vec<Vec<Placement<B>>>>,
+        // The cells sharing an edge with each cell, as a list and as a bitmap.
+        neighbors: Vec<Vec<usize>>,
+        neighbor_bitmaps: Vec<B>,
+        pruning: Pruning,
+        // How the symmetries of the board permute the placements.
+        row_symmetries: Vec<Vec<usize>>,
+        // Order of the symmetry group of the board if the table was restricted
+        // to break the symmetries, see symmetry::break_symmetries.
+        group_order: Option<usize>,
     }
 
-    impl Solver {
-        fn new(board_h: usize, board_w: usize, n: usize, num_solutions: usize) -> Self {
-            let bitmap_size: usize = std::mem::size_of::<Bitmap>()*8;
-            let num_pieces: usize = get_num_pieces(n);
-            let mut table = (0..bitmap_size).map(|_| {
-                (0..num_pieces).map(|_| Vec::<Bitmap>::new()).collect::<Vec<_>>()
+    impl<B: Bitmap> Solver<B> {
+        // `symmetries` are the symmetries of the board.
+        fn new(board_h: usize, board_w: usize, pieces: &PieceSet, options: &SolveOptions, symmetries: &[Vec<usize>])
+               -> Result<Self, SolverError> {
+            let num_cells = board_h*board_w;
+            let copies = options.copies(pieces.len())?;
+            let kind_of: Vec<usize> = copies.iter()
+                .enumerate()
+                .flat_map(|(k,&count)| std::iter::repeat_n(k, count))
+                .collect();
+            let mut table = (0..num_cells).map(|_| {
+                (0..copies.len()).map(|_| Vec::<Placement<B>>::new()).collect::<Vec<_>>()
             }).collect::<Vec<_>>();
 
-            for (k,congruent_figures) in congruent_figures_for_each_piece(n).iter().enumerate() {
+            // Every placement as its cells followed by the piece kind, the same
+            // layout as the rows of the exact cover matrix.
+            let mut placements: Vec<Vec<usize>> = Vec::new();
+            let mut kinds: Vec<usize> = Vec::new();
+            for (k,congruent_figures) in pieces.orientations().iter().enumerate() {
+                if copies[k] == 0 {
+                    continue;
+                }
                 for figure in congruent_figures {
                     let (figure_h,figure_w) = figure.iter().fold((usize::MIN,usize::MIN),
                                                                  |(max_i,max_j),&(i,j)| (max_i.max(i),max_j.max(j)));
-                    let bitmap = figure.iter().map(|&(i,j)| Bitmap::from(1u8) << (i*board_w+j)).sum::<Bitmap>();
                     for offset_i in 0..board_h.saturating_sub(figure_h) {
                         for offset_j in 0..board_w.saturating_sub(figure_w) {
-                            let offset = offset_i*board_w + offset_j;
-                            let lowest_1 = figure[0].1 + offset;
-                            table[lowest_1][k].push(bitmap << offset);
+                            let mut placement: Vec<usize> = figure.iter()
+                                .map(|&(i,j)| (i+offset_i)*board_w + (j+offset_j))
+                                .collect();
+                            placement.push(num_cells + k);
+                            placements.push(placement);
+                            kinds.push(k);
                         }
                     }
                 }
             }
-            Solver { height: board_h,
-                     width: board_w,
-                     num_pieces: num_pieces,
-                     num_solutions: num_solutions,
-                     table: table }
+
+            let row_symmetries = placement_symmetries(&placements, num_cells, symmetries, &copies);
+            let symmetry_break = match options.symmetry_breaking {
+                true => break_symmetries(&kinds, &row_symmetries, &copies),
+                false => None,
+            };
+            let neighbors: Vec<Vec<usize>> = (0..num_cells).map(|c| {
+                let (i,j) = (c / board_w, c % board_w);
+                let mut ret = Vec::with_capacity(4);
+                if i > 0 { ret.push(c - board_w); }
+                if i + 1 < board_h { ret.push(c + board_w); }
+                if j > 0 { ret.push(c - 1); }
+                if j + 1 < board_w { ret.push(c + 1); }
+                ret
+            }).collect();
+            let to_bitmap = |cells: &[usize]| {
+                let mut bitmap = B::empty(num_cells);
+                for &c in cells {
+                    bitmap.insert(c);
+                }
+                bitmap
+            };
+            for (r,placement) in placements.iter().enumerate() {
+                if symmetry_break.as_ref().is_some_and(|b| !b.keep[r]) {
+                    continue;
+                }
+                let cells = &placement[..placement.len()-1];
+                let mut border: Vec<usize> = cells.iter()
+                    .flat_map(|&c| neighbors[c].iter().copied())
+                    .filter(|c| !cells.contains(c))
+                    .collect();
+                border.sort();
+                border.dedup();
+                table[cells[0]][kinds[r]].push(Placement { cells: to_bitmap(cells), border: to_bitmap(&border), row: r });
+            }
+            let neighbor_bitmaps = neighbors.iter().map(|cells| to_bitmap(cells)).collect();
+            Ok(Solver { height: board_h,
+                        width: board_w,
+                        region_unit: pieces.region_unit(&copies),
+                        min_piece_size: kind_of.iter().map(|&k| pieces.piece(k).len()).min().unwrap_or(0),
+                        num_pieces: kind_of.len(),
+                        kind_of,
+                        num_solutions: options.num_solutions,
+                        num_threads: options.threads(),
+                        table,
+                        neighbors,
+                        neighbor_bitmaps,
+                        pruning: options.pruning,
+                        row_symmetries,
+                        group_order: symmetry_break.map(|b| b.group_order) })
+        }
+
+        // Piece i holds the value i, which numbers the copies as solver::solution2board
+        // does since they are placed in order, and the holes hold num_pieces.
+        fn solution2board(&self, solution: &Solution<B>) -> Board {
+            let mut ret = Board(vec![vec![self.num_pieces; self.width]; self.height]);
+            for (i,placement) in solution.0.iter().enumerate() {
+                for c in placement.iter().flat_map(|p| p.cells.cells()) {
+                    *ret.get_mut(c / self.width, c % self.width) = i;
+                }
+            }
+            ret
+        }
+
+        // The placements of a complete solution, as sorted rows.
+        fn solution2rows(&self, solution: &Solution<B>) -> Vec<usize> {
+            let mut rows: Vec<usize> = solution.0.iter().flatten().map(|p| p.row).collect();
+            rows.sort();
+            rows
+        }
+
+        // True if piece i is the next one to place. The copies of a kind are
+        // placed in order, so that swapping them does not make a new solution.
+        fn is_next(&self, solution: &Solution<B>, i: usize) -> bool {
+            solution.0[i].is_none() && (i == 0 || self.kind_of[i-1] != self.kind_of[i] || solution.0[i-1].is_some())
         }
 
-        fn solution2board(&self, solution: &Solution) -> Board {
-            let mut ret = Board::new(self.height, self.width);
-            for (k,bitmap) in solution.0.iter().enumerate() {
-                for (i,row) in ret.0.iter_mut().enumerate() {
-                    for (j,col) in row.iter_mut().enumerate() {
-                        if *bitmap & (Bitmap::from(1u8) << (i*self.width+j)) != Bitmap::from(0u8) {
-                            *col = k;
+        // Calls `visit` with every complete placement of the pieces. Stops as soon
+        // as `visit` breaks or `stop` is set, and sets `stop` in the former case.
+        // `bitmap` is the set of covered cells and is restored before returning.
+        fn backtrack<'a, F>(&'a self,
+                            bitmap: &mut B,
+                            partial_solution: &mut Solution<'a, B>,
+                            stats: &mut SearchStats,
+                            stop: &AtomicBool,
+                            visit: &F) -> ControlFlow<()>
+        where
+            F: Fn(&Solution<B>) -> ControlFlow<()>,
+        {
+            if stop.load(Ordering::Relaxed) {
+                return ControlFlow::Break(());
+            }
+            stats.nodes += 1;
+            if partial_solution.0.iter().all(Option::is_some) {
+                let flow = visit(partial_solution);
+                if flow.is_break() {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                return flow;
+            }
+            let lowest_0 = bitmap.first_empty();
+            for i in 0..self.num_pieces {
+                if self.is_next(partial_solution, i) {
+                    for p in self.table[lowest_0][self.kind_of[i]].iter() {
+                        if self.place(bitmap, p, stats) {
+                            partial_solution.0[i] = Some(p);
+                            let flow = self.backtrack(bitmap, partial_solution, stats, stop, visit);
+                            bitmap.toggle(&p.cells);
+                            partial_solution.0[i] = None;
+                            flow?;
                         }
                     }
                 }
             }
-            ret
+            ControlFlow::Continue(())
+        }
+
+        // Adds `placement` to `bitmap` if it fits and does not lead to a dead end.
+        fn place(&self, bitmap: &mut B, placement: &Placement<B>, stats: &mut SearchStats) -> bool {
+            if !bitmap.is_disjoint(&placement.cells) {
+                return false;
+            }
+            bitmap.toggle(&placement.cells);
+            if self.is_dead_end(bitmap, placement) {
+                stats.pruned += 1;
+                bitmap.toggle(&placement.cells);
+                return false;
+            }
+            true
         }
 
-        fn backtrack(&self, bitmap: Bitmap, partial_solution: &mut Solution, solutions: &mut SolutionSet<Board>) {
-            if self.num_solutions > 0 && solutions.len() >= self.num_solutions {
-                return;
-            }
-            if partial_solution.0.iter().all(|p| *p != Bitmap::from(0u8)) {
-                let solved_board = self.solution2board(partial_solution);
-                solutions.add_solution(solved_board);
-            } else {
-                let lowest_0 = bitmap.trailing_ones() as usize;
-                for i in 0..self.num_pieces {
-                    if partial_solution.0[i] == Bitmap::from(0u8) {
-                        for &p in self.table[lowest_0][i].iter() {
-                            if bitmap & p == Bitmap::from(0u8) {
-                                partial_solution.0[i] = p;
-                                self.backtrack(bitmap|p, partial_solution, solutions);
-                                partial_solution.0[i] = Bitmap::from(0u8);
+        // Walks the search tree level by level until it has at least `min_subtrees`
+        // nodes on one level, or runs out of levels, and returns that level. Only
+        // the nodes it expands go into `stats`: backtrack counts the ones returned
+        // when it starts from them.
+        fn split<'a>(&'a self, initial_bits: B, min_subtrees: usize, stats: &mut SearchStats)
+                     -> Vec<(B, Solution<'a, B>)> {
+            let mut level = vec![(initial_bits, Solution::new(self.num_pieces))];
+            while level.len() < min_subtrees {
+                let mut next_level = Vec::new();
+                let mut expanded = false;
+                for (bitmap, solution) in level {
+                    if solution.0.iter().all(Option::is_some) {
+                        next_level.push((bitmap, solution));
+                        continue;
+                    }
+                    expanded = true;
+                    stats.nodes += 1;
+                    let lowest_0 = bitmap.first_empty();
+                    for i in 0..self.num_pieces {
+                        if self.is_next(&solution, i) {
+                            for p in self.table[lowest_0][self.kind_of[i]].iter() {
+                                let mut child_bitmap = bitmap.clone();
+                                if self.place(&mut child_bitmap, p, stats) {
+                                    let mut child = solution.clone();
+                                    child.0[i] = Some(p);
+                                    next_level.push((child_bitmap, child));
+                                }
                             }
                         }
                     }
                 }
+                level = next_level;
+                if !expanded {
+                    break;
+                }
+            }
+            level
+        }
+
+        // Runs backtrack on self.num_threads threads. The calling thread splits off
+        // the top of the search tree, and the workers take the subtrees below it one
+        // at a time until there are none left. `visit` may be called from any of
+        // them, and breaking from it stops all of them.
+        fn run<F>(&self, initial_bits: B, visit: F) -> SearchStats
+        where
+            F: Fn(&Solution<B>) -> ControlFlow<()> + Sync,
+        {
+            let stop = AtomicBool::new(false);
+            let mut stats = SearchStats::default();
+            if self.num_threads <= 1 {
+                let mut bitmap = initial_bits;
+                let mut solution = Solution::new(self.num_pieces);
+                let _ = self.backtrack(&mut bitmap, &mut solution, &mut stats, &stop, &visit);
+                return stats;
+            }
+            let subtrees = self.split(initial_bits, self.num_threads * 16, &mut stats);
+            let next = AtomicUsize::new(0);
+            thread::scope(|scope| {
+                let workers: Vec<_> = (0..self.num_threads.min(subtrees.len())).map(|_| {
+                    scope.spawn(|| {
+                        let mut stats = SearchStats::default();
+                        loop {
+                            let k = next.fetch_add(1, Ordering::Relaxed);
+                            if k >= subtrees.len() {
+                                break;
+                            }
+                            let (mut bitmap, mut solution) = subtrees[k].clone();
+                            if self.backtrack(&mut bitmap, &mut solution, &mut stats, &stop, &visit).is_break() {
+                                break;
+                            }
+                        }
+                        stats
+                    })
+                }).collect();
+                for worker in workers {
+                    let worker_stats = worker.join().unwrap();
+                    stats.nodes += worker_stats.nodes;
+                    stats.pruned += worker_stats.pruned;
+                }
+            });
+            stats
+        }
+
+        // True if `placement`, which has just been added to `bitmap`, leaves open
+        // cells that no piece can fill, as far as self.pruning looks.
+        fn is_dead_end(&self, bitmap: &B, placement: &Placement<B>) -> bool {
+            match self.pruning {
+                Pruning::Off => false,
+                Pruning::IsolatedCells => self.isolates_cell(bitmap, placement),
+                Pruning::Regions => self.isolates_cell(bitmap, placement) || self.cuts_off_region(bitmap, placement),
             }
         }
 
-        fn solve(&self, initial_bits: Bitmap) -> Vec<Board> {
-            let mut solution = Solution::new(self.num_pieces);
-            let mut solutions = SolutionSet::new();
-            self.backtrack(initial_bits.clone(), &mut solution, &mut solutions);
-            solutions.get_solutions()
+        // A monomino can fill any isolated cell.
+        fn isolates_cell(&self, bitmap: &B, placement: &Placement<B>) -> bool {
+            self.min_piece_size > 1
+                && placement.border.cells().any(|c| !bitmap.contains(c) && self.neighbor_bitmaps[c].is_subset(bitmap))
+        }
+
+        // The regions of open cells untouched by `placement` were already checked
+        // before it was placed, so only the ones next to it are flooded.
+        fn cuts_off_region(&self, bitmap: &B, placement: &Placement<B>) -> bool {
+            let mut seen = bitmap.clone();
+            let mut stack = Vec::new();
+            for start in placement.border.cells() {
+                if seen.contains(start) {
+                    continue;
+                }
+                seen.insert(start);
+                stack.push(start);
+                let mut size = 0;
+                while let Some(cell) = stack.pop() {
+                    size += 1;
+                    for &next in &self.neighbors[cell] {
+                        if !seen.contains(next) {
+                            seen.insert(next);
+                            stack.push(next);
+                        }
+                    }
+                }
+                if size % self.region_unit != 0 || size < self.min_piece_size {
+                    return true;
+                }
+            }
+            false
+        }
+
+        fn solve(&self, initial_bits: B) -> (Vec<Board>, SearchStats) {
+            // The distinct solutions, plus the canonical rows of the ones seen so
+            // far unless the table already rules out the congruent ones.
+            let found = Mutex::new((Vec::new(), HashSet::new()));
+            let limit_reached = |num_found: usize| self.num_solutions > 0 && num_found >= self.num_solutions;
+            let stats = self.run(initial_bits, |partial_solution| {
+                let mut found = found.lock().unwrap();
+                let (distinct, seen) = &mut *found;
+                if limit_reached(distinct.len()) {
+                    return ControlFlow::Break(());
+                }
+                if self.group_order.is_none()
+                    && !seen.insert(canonical(&self.solution2rows(partial_solution), &self.row_symmetries)) {
+                    return ControlFlow::Continue(());
+                }
+                distinct.push(self.solution2board(partial_solution));
+                if limit_reached(distinct.len()) {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            (found.into_inner().unwrap().0, stats)
+        }
+
+        fn count(&self, initial_bits: B) -> (SolutionCount, SearchStats) {
+            if let Some(group_order) = self.group_order {
+                let distinct = AtomicUsize::new(0);
+                let stats = self.run(initial_bits, |_| {
+                    distinct.fetch_add(1, Ordering::Relaxed);
+                    ControlFlow::Continue(())
+                });
+                let distinct = distinct.into_inner();
+                return (SolutionCount { raw: distinct * group_order, distinct }, stats);
+            }
+            let counter = Mutex::new(SolutionCounter::new(self.row_symmetries.len()));
+            let stats = self.run(initial_bits, |partial_solution| {
+                let stabilizer = stabilizer(&self.solution2rows(partial_solution), &self.row_symmetries);
+                counter.lock().unwrap().add(stabilizer);
+                ControlFlow::Continue(())
+            });
+            let count = counter.into_inner().unwrap().get_count();
+            (count, stats)
         }
     }
 
-    fn board2bitmap(board: &Vec<Vec<usize>>) -> Bitmap {
-        let width = board[0].len();
-        let mut hole_coordinates = Vec::new();
+    fn board2bitmap<B: Bitmap>(board: &[Vec<usize>], width: usize) -> B {
+        let mut b = B::empty(board.len()*width);
         for (i,row) in board.iter().enumerate() {
             for (j,col) in row.iter().enumerate() {
                 if *col != 0 {
-                    hole_coordinates.push((i,j));
+                    b.insert(i*width+j);
                 }
             }
         }
-        let b = hole_coordinates.iter().map(|&(i,j)| Bitmap::from(1u8) << (i*width+j)).sum::<Bitmap>();
         b
     }
 
-    pub fn solve_polyomino_bt(board: &Vec<Vec<usize>>, n: usize) -> Vec<Board> {
-        let num_solutions: usize = if n >= 6 { 1 } else { 0 };
-        let solver = Solver::new(board.len(), board[0].len(), n, num_solutions);
-        let solutions = solver.solve(board2bitmap(&board));
-        solutions
+    fn solve_with_bitmap<B: Bitmap>(board: &[Vec<usize>], height: usize, width: usize, pieces: &PieceSet,
+                                    options: &SolveOptions) -> Result<(Vec<Board>, SearchStats), SolverError> {
+        let solver = Solver::<B>::new(height, width, pieces, options, &Board(board.to_vec()).get_symmetries())?;
+        Ok(solver.solve(board2bitmap(board, width)))
+    }
+
+    fn count_with_bitmap<B: Bitmap>(board: &[Vec<usize>], height: usize, width: usize, pieces: &PieceSet,
+                                    options: &SolveOptions) -> Result<(SolutionCount, SearchStats), SolverError> {
+        let solver = Solver::<B>::new(height, width, pieces, options, &Board(board.to_vec()).get_symmetries())?;
+        Ok(solver.count(board2bitmap(board, width)))
+    }
+
+    pub fn solve_polyomino_bt(board: &[Vec<usize>], pieces: impl IntoPieceSet) -> Result<Vec<Board>, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        solve_polyomino_bt_with(board, &pieces, &SolveOptions::for_size(pieces.max_size()))
+    }
+
+    pub fn solve_polyomino_bt_with(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                                   -> Result<Vec<Board>, SolverError> {
+        solve_polyomino_bt_stats(board, pieces, options).map(|(solutions,_)| solutions)
+    }
+
+    /// Same as solve_polyomino_bt_with, and also reports how much work the search
+    /// did. The bitmap is the smallest of u64, u128, u256 and BitSet that holds
+    /// the board.
+    pub fn solve_polyomino_bt_stats(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                                    -> Result<(Vec<Board>, SearchStats), SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (height,width) = validate_board(board, pieces.area(&copies), pieces.region_unit(&copies))?;
+        match height*width {
+            cells if cells <= u64::CAPACITY => solve_with_bitmap::<u64>(board, height, width, &pieces, options),
+            cells if cells <= u128::CAPACITY => solve_with_bitmap::<u128>(board, height, width, &pieces, options),
+            cells if cells <= u256::CAPACITY => solve_with_bitmap::<u256>(board, height, width, &pieces, options),
+            _ => solve_with_bitmap::<BitSet>(board, height, width, &pieces, options),
+        }
+    }
+
+    /// Counts the solutions of a polyomino puzzle, all of them and up to
+    /// congruence, without building a Board for any of them. The solution limit
+    /// in `options` is ignored.
+    pub fn count_polyomino_bt(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                              -> Result<SolutionCount, SolverError> {
+        count_polyomino_bt_stats(board, pieces, options).map(|(count,_)| count)
+    }
+
+    /// Same as count_polyomino_bt, and also reports how much work the search did.
+    pub fn count_polyomino_bt_stats(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                                    -> Result<(SolutionCount, SearchStats), SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (height,width) = validate_board(board, pieces.area(&copies), pieces.region_unit(&copies))?;
+        match height*width {
+            cells if cells <= u64::CAPACITY => count_with_bitmap::<u64>(board, height, width, &pieces, options),
+            cells if cells <= u128::CAPACITY => count_with_bitmap::<u128>(board, height, width, &pieces, options),
+            cells if cells <= u256::CAPACITY => count_with_bitmap::<u256>(board, height, width, &pieces, options),
+            _ => count_with_bitmap::<BitSet>(board, height, width, &pieces, options),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::testset::testset::test_board;
+
+        #[test]
+        fn same_count_with_every_bitmap() {
+            let board = test_board("501").unwrap();
+            let (pieces, options) = (PieceSet::polyominoes(5).unwrap(), SolveOptions::for_size(5));
+            let counts = [
+                count_with_bitmap::<u64>(&board, 8, 8, &pieces, &options).unwrap(),
+                count_with_bitmap::<u128>(&board, 8, 8, &pieces, &options).unwrap(),
+                count_with_bitmap::<u256>(&board, 8, 8, &pieces, &options).unwrap(),
+                count_with_bitmap::<BitSet>(&board, 8, 8, &pieces, &options).unwrap(),
+            ];
+            for (count,stats) in counts {
+                assert_eq!((count.distinct, count.raw), (65, 520));
+                assert_eq!(stats, counts[0].1);
+            }
+        }
+
+        #[test]
+        fn threaded_matches_single_thread() {
+            // Which of the congruent solutions the table leaves in, without
+            // symmetry breaking, depends on which thread gets there first, so
+            // the solutions are compared with all their transformations.
+            let board = test_board("501").unwrap();
+            let congruent = |solutions: Vec<Board>| -> HashSet<Board> {
+                solutions.iter().flat_map(|solution| solution.get_all_transformations()).collect()
+            };
+            for symmetry_breaking in [true, false] {
+                let single = SolveOptions { symmetry_breaking, ..SolveOptions::for_size(5) };
+                let threaded = SolveOptions { num_threads: 3, ..single.clone() };
+                let expected = solve_polyomino_bt_with(&board, 5, &single).unwrap();
+                let solutions = solve_polyomino_bt_with(&board, 5, &threaded).unwrap();
+                assert_eq!((expected.len(), solutions.len()), (65, 65));
+                assert!(congruent(solutions) == congruent(expected));
+                let count = count_polyomino_bt(&board, 5, &threaded).unwrap();
+                assert_eq!((count.distinct, count.raw), (65, 520));
+            }
+        }
+
+        #[test]
+        fn board_past_256_cells() {
+            // The 8x8 board with its centre hole in the middle of an 18x18 one,
+            // 324 cells in all, which only BitSet holds.
+            let mut board = vec![vec![1; 18]; 18];
+            for (i,row) in test_board("501").unwrap().into_iter().enumerate() {
+                board[i + 5][5..13].copy_from_slice(&row);
+            }
+            let count = count_polyomino_bt(&board, 5, &SolveOptions::for_size(5)).unwrap();
+            assert_eq!((count.distinct, count.raw), (65, 520));
+            assert_eq!(solve_polyomino_bt(&board, 5).unwrap().len(), 65);
+        }
     }
 }
@@ -0,0 +1,175 @@
+pub mod bitmap {
+    use ethnum::u256;
+
+    /// A set of board cells, the state of the backtracking solver. Fixed-width
+    /// integers are the fastest; BitSet takes boards of any size.
+    pub trait Bitmap: Clone + PartialEq + Send + Sync {
+        /// Largest number of cells the type can hold.
+        const CAPACITY: usize;
+
+        /// An empty set with room for `num_cells` cells.
+        fn empty(num_cells: usize) -> Self;
+
+        fn insert(&mut self, cell: usize);
+
+        fn contains(&self, cell: usize) -> bool;
+
+        fn is_disjoint(&self, other: &Self) -> bool;
+
+        fn is_subset(&self, other: &Self) -> bool;
+
+        /// Flips every cell of `other`: adds `other` to a set it is disjoint
+        /// from and takes it out of a set that contains it.
+        fn toggle(&mut self, other: &Self);
+
+        /// The lowest cell not in the set.
+        fn first_empty(&self) -> usize;
+
+        /// The cells in the set, in increasing order.
+        fn cells(&self) -> impl Iterator<Item = usize> + '_;
+    }
+
+    macro_rules! impl_bitmap_for_uint {
+        ($($t:ty),*) => {$(
+            impl Bitmap for $t {
+                const CAPACITY: usize = <$t>::BITS as usize;
+
+                fn empty(_num_cells: usize) -> Self {
+                    <$t>::from(0u8)
+                }
+
+                fn insert(&mut self, cell: usize) {
+                    *self |= <$t>::from(1u8) << cell;
+                }
+
+                fn contains(&self, cell: usize) -> bool {
+                    (*self >> cell) & <$t>::from(1u8) != <$t>::from(0u8)
+                }
+
+                fn is_disjoint(&self, other: &Self) -> bool {
+                    *self & *other == <$t>::from(0u8)
+                }
+
+                fn is_subset(&self, other: &Self) -> bool {
+                    *self & *other == *self
+                }
+
+                fn toggle(&mut self, other: &Self) {
+                    *self ^= *other;
+                }
+
+                fn first_empty(&self) -> usize {
+                    self.trailing_ones() as usize
+                }
+
+                fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+                    let mut bits = *self;
+                    std::iter::from_fn(move || {
+                        if bits == <$t>::from(0u8) {
+                            return None;
+                        }
+                        let cell = bits.trailing_zeros() as usize;
+                        bits &= bits - <$t>::from(1u8);
+                        Some(cell)
+                    })
+                }
+            }
+        )*};
+    }
+
+    impl_bitmap_for_uint!(u64, u128, u256);
+
+    /// Heap-allocated bitmap made of as many 64-bit words as the board needs.
+    #[derive(Clone,Debug,PartialEq,Eq)]
+    pub struct BitSet(Vec<u64>);
+
+    impl Bitmap for BitSet {
+        const CAPACITY: usize = usize::MAX;
+
+        fn empty(num_cells: usize) -> Self {
+            BitSet(vec![0; num_cells.div_ceil(64)])
+        }
+
+        fn insert(&mut self, cell: usize) {
+            self.0[cell / 64].insert(cell % 64);
+        }
+
+        fn contains(&self, cell: usize) -> bool {
+            self.0[cell / 64].contains(cell % 64)
+        }
+
+        fn is_disjoint(&self, other: &Self) -> bool {
+            self.0.iter().zip(&other.0).all(|(a,b)| a & b == 0)
+        }
+
+        fn is_subset(&self, other: &Self) -> bool {
+            self.0.iter().zip(&other.0).all(|(a,b)| a & b == *a)
+        }
+
+        fn toggle(&mut self, other: &Self) {
+            for (a,b) in self.0.iter_mut().zip(&other.0) {
+                *a ^= b;
+            }
+        }
+
+        fn first_empty(&self) -> usize {
+            match self.0.iter().position(|&word| word != u64::MAX) {
+                Some(w) => w*64 + self.0[w].first_empty(),
+                None => self.0.len()*64,
+            }
+        }
+
+        fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+            self.0.iter()
+                .enumerate()
+                .flat_map(|(w,word)| word.cells().map(move |cell| w*64 + cell))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::collections::BTreeSet;
+
+        // Runs the operations of Bitmap on two sets of `num_cells` cells and
+        // checks them against BTreeSets of the same cells.
+        fn check<B: Bitmap>(num_cells: usize) {
+            let a_cells: BTreeSet<usize> = (0..num_cells).filter(|c| c % 3 == 0 || c % 7 == 1).collect();
+            let b_cells: BTreeSet<usize> = (0..num_cells).filter(|c| c % 3 == 1).collect();
+            let (mut a, mut b) = (B::empty(num_cells), B::empty(num_cells));
+            a_cells.iter().for_each(|&c| a.insert(c));
+            b_cells.iter().for_each(|&c| b.insert(c));
+
+            assert_eq!(a.cells().collect::<BTreeSet<_>>(), a_cells);
+            assert!((0..num_cells).all(|c| a.contains(c) == a_cells.contains(&c)));
+            assert_eq!(a.is_disjoint(&b), a_cells.is_disjoint(&b_cells));
+            assert!(!a.is_subset(&b));
+            assert_eq!(a.first_empty(), 2);
+
+            // A set of its own cells only, then the union of a and b.
+            let mut c = B::empty(num_cells);
+            b_cells.iter().filter(|&&cell| !a_cells.contains(&cell)).for_each(|&cell| c.insert(cell));
+            assert!(a.is_disjoint(&c) && c.is_subset(&b));
+            a.toggle(&c);
+            let union: BTreeSet<usize> = a_cells.union(&b_cells).copied().collect();
+            assert_eq!(a.cells().collect::<BTreeSet<_>>(), union);
+            assert_eq!(a.first_empty(), (0..num_cells).find(|c| !union.contains(c)).unwrap_or(num_cells));
+            a.toggle(&c);
+            assert_eq!(a.cells().collect::<BTreeSet<_>>(), a_cells);
+
+            let mut full = B::empty(num_cells);
+            (0..num_cells).for_each(|cell| full.insert(cell));
+            assert_eq!(full.first_empty(), num_cells);
+        }
+
+        #[test]
+        fn every_bitmap_type() {
+            check::<u64>(64);
+            check::<u128>(128);
+            check::<u128>(100);
+            check::<u256>(256);
+            check::<BitSet>(64);
+            check::<BitSet>(300);
+        }
+    }
+}
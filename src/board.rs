@@ -1,5 +1,6 @@
 pub mod board {
     use colored::Colorize;
+    use crate::palette::palette::{board_colors, HOLE_COLOR, PALETTE};
     use crate::solutionset::solutionset::Transformable;
     
     #[derive(Clone,Eq,PartialEq,Hash)]
@@ -43,6 +44,7 @@ pub mod board {
             Board(ret)
         }
 
+        #[allow(clippy::needless_range_loop)]
         fn flip_diagonally(&self) -> Self {
             let size = self.width();
             let mut ret = vec![vec![0;size]; size];
@@ -54,6 +56,14 @@ pub mod board {
             Board(ret)
         }
 
+        /// Where the cell (i,j) ends up after transform(vertically, horizontally, diagonally).
+        pub fn transform_position(&self, i: usize, j: usize,
+                                  vertically: bool, horizontally: bool, diagonally: bool) -> (usize,usize) {
+            let i = if vertically { self.height() - 1 - i } else { i };
+            let j = if horizontally { self.width() - 1 - j } else { j };
+            if diagonally { (j,i) } else { (i,j) }
+        }
+
         pub fn is_square(&self) -> bool {
             self.width() == self.height()
         }
@@ -97,54 +107,16 @@ pub mod board {
             ret
         }
 
-        fn num2color(cell: usize) -> (u8,u8,u8) {
-            let (r,g,b) = match cell {
-                0 => (255, 0, 0), // Red
-                1 => (0, 255, 0), // Green
-                2 => (0, 0, 255), // Blue
-                3 => (255, 255, 0), // Yellow
-                4 => (255, 165, 0), // Orange
-                5 => (128, 0, 128), // Purple
-                6 => (0, 255, 255), // Cyan
-                7 => (255, 20, 147), // Deep Pink
-                8 => (0, 128, 0), // Dark Green
-                9 => (75, 0, 130), // Indigo
-                10 => (255, 192, 203), // Pink
-                11 => (139, 69, 19), // Saddle Brown
-                12 => (169, 169, 169), // Dark Gray
-                13 => (255, 69, 0), // Red-Orange
-                14 => (0, 0, 128), // Navy
-                15 => (255, 105, 180), // Hot Pink
-                16 => (255, 255, 224), // Light Yellow
-                17 => (0, 128, 128), // Teal
-                18 => (165, 42, 42), // Brown
-                19 => (210, 105, 30), // Chocolate
-                20 => (124, 252, 0), // Lawn Green
-                21 => (0, 255, 127), // Spring Green
-                22 => (0, 100, 0), // Dark Green
-                23 => (127, 255, 212), // Aquamarine
-                24 => (72, 61, 139), // Dark Slate Blue
-                25 => (220, 20, 60), // Crimson
-                26 => (173, 255, 47), // Green-Yellow
-                27 => (255, 140, 0), // Dark Orange
-                28 => (255, 99, 71), // Tomato
-                29 => (144, 238, 144), // Light Green
-                30 => (70, 130, 180), // Steel Blue
-                31 => (135, 206, 250), // Light Sky Blue
-                32 => (46, 139, 87), // Sea Green
-                33 => (255, 215, 0), // Gold
-                34 => (0, 191, 255), // Deep Sky Blue
-                35 => (238, 130, 238), // Violet
-                _  => (128, 0, 0), // Maroon
-            };
-            (r,g,b)
-        }
-        
-        pub fn pprint(&self) {
+        /// Prints the board in color, the pieces that touch having different
+        /// colors from palette::PALETTE and the cells holding `hole` as gray
+        /// dots, smaller than the others so that they still stand out when the
+        /// terminal shows no color.
+        pub fn pprint(&self, hole: usize) {
+            let colors = board_colors(self, &PALETTE, |value| value != hole);
             for row in self.0.iter() {
                 for value in row.iter() {
-                    let cell = "●";
-                    let (r,g,b) = Board::num2color(*value);
+                    let cell = if *value == hole { "·" } else { "●" };
+                    let (r,g,b) = colors.get(value).copied().unwrap_or(HOLE_COLOR);
                     let color_cell = cell.truecolor(r,g,b);
                     print!("{}", color_cell);
                 }
@@ -172,5 +144,57 @@ pub mod board {
 
             transformations
         }
+
+        fn get_rotations(&self) -> Vec<Self> {
+            let mut rotations = Vec::new();
+            let diagonal_opt = if self.is_square() {
+                vec![true, false]
+            } else {
+                vec![false]
+            };
+
+            for vertically in [true, false] {
+                for horizontally in [true, false] {
+                    for &diagonally in &diagonal_opt {
+                        // An odd number of flips is a mirror image.
+                        if vertically ^ horizontally ^ diagonally {
+                            continue;
+                        }
+                        rotations.push(self.transform(vertically, horizontally, diagonally));
+                    }
+                }
+            }
+
+            rotations
+        }
+
+        fn get_symmetries(&self) -> Vec<Vec<usize>> {
+            let mut symmetries = Vec::new();
+            let diagonal_opt = if self.is_square() {
+                vec![true, false]
+            } else {
+                vec![false]
+            };
+
+            for vertically in [true, false] {
+                for horizontally in [true, false] {
+                    for &diagonally in &diagonal_opt {
+                        if self.transform(vertically, horizontally, diagonally) != *self {
+                            continue;
+                        }
+                        let mut permutation = Vec::with_capacity(self.height()*self.width());
+                        for i in 0..self.height() {
+                            for j in 0..self.width() {
+                                let (a,b) = self.transform_position(i, j, vertically, horizontally, diagonally);
+                                permutation.push(a*self.width() + b);
+                            }
+                        }
+                        symmetries.push(permutation);
+                    }
+                }
+            }
+
+            symmetries
+        }
     }
 }
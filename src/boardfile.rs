@@ -0,0 +1,176 @@
+pub mod boardfile {
+    use std::fmt;
+    use std::path::Path;
+
+    // Plain-text puzzle files. Every line is a row of the board, '.' is an open
+    // cell and '#' is a hole ('0' and '1' are accepted as well). A cube is a
+    // stack of such boards separated by blank lines, first layer first.
+    //
+    //   ...#      ...
+    //   ....      ...
+    //
+    //             .#.
+    //             ...
+    //
+    // A pieces file lists polyominoes the same way, one grid per piece, with '#'
+    // for the cells of the piece. The grids may have different shapes.
+
+    const OPEN: char = '.';
+    const HOLE: char = '#';
+
+    #[derive(Debug)]
+    pub enum BoardFileError {
+        Io(std::io::Error),
+        Parse { line: usize, message: String },
+    }
+
+    impl fmt::Display for BoardFileError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                BoardFileError::Io(e) => write!(f, "{}", e),
+                BoardFileError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            }
+        }
+    }
+
+    impl std::error::Error for BoardFileError {}
+
+    impl From<std::io::Error> for BoardFileError {
+        fn from(e: std::io::Error) -> Self {
+            BoardFileError::Io(e)
+        }
+    }
+
+    fn parse_error(line: usize, message: String) -> BoardFileError {
+        BoardFileError::Parse { line, message }
+    }
+
+    fn parse_row(line_no: usize, line: &str) -> Result<Vec<usize>, BoardFileError> {
+        line.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c {
+                OPEN | '0' => Ok(0),
+                HOLE | '1' => Ok(1),
+                _ => Err(parse_error(line_no, format!("unexpected character '{}'", c))),
+            })
+            .collect()
+    }
+
+    type Grid = Vec<Vec<usize>>;
+
+    // The rectangular grids of `text` separated by blank lines, each with the
+    // number of the line that ends it.
+    fn parse_grids(text: &str) -> Result<Vec<(usize, Grid)>, BoardFileError> {
+        let mut grids: Vec<(usize, Grid)> = Vec::new();
+        let mut grid: Grid = Vec::new();
+        let lines = text.lines().map(|line| line.trim()).chain(std::iter::once(""));
+        for (i,line) in lines.enumerate() {
+            let line_no = i + 1;
+            if !line.is_empty() {
+                let row = parse_row(line_no, line)?;
+                if let Some(first) = grid.first() {
+                    if row.len() != first.len() {
+                        return Err(parse_error(line_no, format!("row has {} cells, expected {}",
+                                                                 row.len(), first.len())));
+                    }
+                }
+                grid.push(row);
+            } else if !grid.is_empty() {
+                grids.push((line_no, std::mem::take(&mut grid)));
+            }
+        }
+        if grids.is_empty() {
+            return Err(parse_error(1, "no cells found".to_string()));
+        }
+        Ok(grids)
+    }
+
+    pub fn parse_cube(text: &str) -> Result<Vec<Vec<Vec<usize>>>, BoardFileError> {
+        let mut layers: Vec<Vec<Vec<usize>>> = Vec::new();
+        for (line_no,layer) in parse_grids(text)? {
+            if let Some(first) = layers.first() {
+                let (shape, layer_shape) = ((first.len(), first[0].len()), (layer.len(), layer[0].len()));
+                if shape != layer_shape {
+                    return Err(parse_error(line_no, format!("layer is {}x{}, expected {}x{}",
+                                                             layer_shape.0, layer_shape.1, shape.0, shape.1)));
+                }
+            }
+            layers.push(layer);
+        }
+        Ok(layers)
+    }
+
+    /// The grids of a pieces file, one per piece, for PieceSet::from_boards.
+    pub fn parse_pieces(text: &str) -> Result<Vec<Vec<Vec<usize>>>, BoardFileError> {
+        Ok(parse_grids(text)?.into_iter().map(|(_,grid)| grid).collect())
+    }
+
+    pub fn parse_board(text: &str) -> Result<Vec<Vec<usize>>, BoardFileError> {
+        let mut layers = parse_cube(text)?;
+        if layers.len() > 1 {
+            return Err(parse_error(layers[0].len() + 1,
+                                   format!("found {} layers, a board has only one", layers.len())));
+        }
+        Ok(layers.remove(0))
+    }
+
+    pub fn write_board(board: &[Vec<usize>]) -> String {
+        let mut ret = String::new();
+        for row in board {
+            ret.extend(row.iter().map(|&value| if value == 0 { OPEN } else { HOLE }));
+            ret.push('\n');
+        }
+        ret
+    }
+
+    pub fn write_cube(cube: &[Vec<Vec<usize>>]) -> String {
+        cube.iter()
+            .map(|layer| write_board(layer))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn read_board_file<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<usize>>, BoardFileError> {
+        parse_board(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn read_cube_file<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<Vec<usize>>>, BoardFileError> {
+        parse_cube(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn read_pieces_file<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<Vec<usize>>>, BoardFileError> {
+        parse_pieces(&std::fs::read_to_string(path)?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn board_round_trip() {
+            let text = "...#\n....\n#..#\n";
+            let board = parse_board(text).unwrap();
+            assert_eq!(board, vec![vec![0, 0, 0, 1], vec![0, 0, 0, 0], vec![1, 0, 0, 1]]);
+            assert_eq!(write_board(&board), text);
+            assert_eq!(parse_board("0001\n0000\n1001\n").unwrap(), board);
+        }
+
+        #[test]
+        fn cube_round_trip() {
+            let text = "...\n.#.\n\n##.\n...\n";
+            let cube = parse_cube(text).unwrap();
+            assert_eq!(cube, vec![vec![vec![0, 0, 0], vec![0, 1, 0]], vec![vec![1, 1, 0], vec![0, 0, 0]]]);
+            assert_eq!(write_cube(&cube), text);
+            assert_eq!(parse_cube(&write_cube(&cube[..1])).unwrap(), cube[..1]);
+        }
+
+        #[test]
+        fn parse_errors() {
+            assert!(matches!(parse_board("..\n...\n"), Err(BoardFileError::Parse { line: 2, .. })));
+            assert!(matches!(parse_board("..\nx.\n"), Err(BoardFileError::Parse { line: 2, .. })));
+            assert!(matches!(parse_board("..\n\n..\n"), Err(BoardFileError::Parse { .. })));
+            assert!(matches!(parse_cube("..\n\n...\n"), Err(BoardFileError::Parse { line: 4, .. })));
+            assert!(matches!(parse_cube("\n\n"), Err(BoardFileError::Parse { .. })));
+        }
+    }
+}
@@ -1,6 +1,9 @@
 pub mod cube {
     use crate::polycubelist::polycubelist::*;
     use crate::solutionset::solutionset::Transformable;
+    use crate::pieceset::pieceset::{PieceSet, Orientation};
+    use crate::palette::palette::{cube_colors, HOLE_COLOR, PALETTE};
+    use colored::Colorize;
     
     #[derive(Clone,Eq,PartialEq,Hash,Debug)]
     pub struct Cube(pub Vec<Vec<Vec<usize>>>);
@@ -14,99 +17,118 @@ pub mod cube {
             &mut self.0[i][j][k]
         }
         
-        fn rotate_k(&self, k: usize) -> Cube {
-            let dim0 = self.0.len();
-            let dim1 = self.0[0].len();
-            let dim2 = self.0[0][0].len();
-            
-            let mut rotated = vec![vec![vec![0; dim2]; dim1]; dim0];
+        fn shape(&self) -> (usize,usize,usize) {
+            (self.0.len(), self.0[0].len(), self.0[0][0].len())
+        }
+
+        // Where the cell (x,y,z) ends up after rotation number k.
+        fn rotate_position(&self, k: usize, x: usize, y: usize, z: usize) -> (usize,usize,usize) {
+            let (dim0,dim1,dim2) = self.shape();
+            match k {
+                0  => (       x,       y,       z),
+                1  => (dim0-1-x,       z,       y),
+                2  => (       x,dim1-1-z,       y),
+                3  => (       x,       z,dim2-1-y),
+                4  => (dim0-1-y,       x,       z),
+                5  => (       y,dim1-1-x,       z),
+                6  => (       y,       x,dim2-1-z),
+                7  => (       y,       z,       x),
+                8  => (       z,       x,       y),
+                9  => (dim0-1-z,       y,       x),
+                10 => (       z,dim1-1-y,       x),
+                11 => (       z,       y,dim2-1-x),
+                12 => (       x,dim1-1-y,dim2-1-z),
+                13 => (dim0-1-x,       y,dim2-1-z),
+                14 => (dim0-1-x,dim1-1-y,       z),
+                15 => (dim0-1-x,dim1-1-z,dim2-1-y),
+                16 => (dim0-1-y,dim1-1-x,dim2-1-z),
+                17 => (       y,dim1-1-z,dim2-1-x),
+                18 => (dim0-1-y,       z,dim2-1-x),
+                19 => (dim0-1-y,dim1-1-z,       x),
+                20 => (       z,dim1-1-x,dim2-1-y),
+                21 => (dim0-1-z,       x,dim2-1-y),
+                22 => (dim0-1-z,dim1-1-x,       y),
+                23 => (dim0-1-z,dim1-1-y,dim2-1-x),
+                _  => (x,y,z),
+            }
+        }
+
+        // Where the cell (x,y,z) ends up after rotation number k, of the mirror
+        // image of the cuboid if `mirrored`.
+        fn transform_position(&self, k: usize, mirrored: bool, x: usize, y: usize, z: usize) -> (usize,usize,usize) {
+            let z = if mirrored { self.shape().2 - 1 - z } else { z };
+            self.rotate_position(k, x, y, z)
+        }
+
+        fn transform(&self, k: usize, mirrored: bool) -> Cube {
+            let (dim0,dim1,dim2) = self.shape();
+            let mut transformed = vec![vec![vec![0; dim2]; dim1]; dim0];
             for x in 0..dim0 {
                 for y in 0..dim1 {
                     for z in 0..dim2 {
-                        match k {
-                            0  => rotated[       x][       y][       z] = self.0[x][y][z],
-                            1  => rotated[dim0-1-x][       z][       y] = self.0[x][y][z],
-                            2  => rotated[       x][dim1-1-z][       y] = self.0[x][y][z],
-                            3  => rotated[       x][       z][dim2-1-y] = self.0[x][y][z],
-                            4  => rotated[dim0-1-y][       x][       z] = self.0[x][y][z],
-                            5  => rotated[       y][dim1-1-x][       z] = self.0[x][y][z],
-                            6  => rotated[       y][       x][dim2-1-z] = self.0[x][y][z],
-                            7  => rotated[       y][       z][       x] = self.0[x][y][z],
-                            8  => rotated[       z][       x][       y] = self.0[x][y][z],
-                            9  => rotated[dim0-1-z][       y][       x] = self.0[x][y][z],
-                            10 => rotated[       z][dim1-1-y][       x] = self.0[x][y][z],
-                            11 => rotated[       z][       y][dim2-1-x] = self.0[x][y][z],
-                            12 => rotated[       x][dim1-1-y][dim2-1-z] = self.0[x][y][z],
-                            13 => rotated[dim0-1-x][       y][dim2-1-z] = self.0[x][y][z],
-                            14 => rotated[dim0-1-x][dim1-1-y][       z] = self.0[x][y][z],
-                            15 => rotated[dim0-1-x][dim1-1-z][dim2-1-y] = self.0[x][y][z],
-                            16 => rotated[dim0-1-y][dim1-1-x][dim2-1-z] = self.0[x][y][z],
-                            17 => rotated[       y][dim1-1-z][dim2-1-x] = self.0[x][y][z],
-                            18 => rotated[dim0-1-y][       z][dim2-1-x] = self.0[x][y][z],
-                            19 => rotated[dim0-1-y][dim1-1-z][       x] = self.0[x][y][z],
-                            20 => rotated[       z][dim1-1-x][dim2-1-y] = self.0[x][y][z],
-                            21 => rotated[dim0-1-z][       x][dim2-1-y] = self.0[x][y][z],
-                            22 => rotated[dim0-1-z][dim1-1-x][       y] = self.0[x][y][z],
-                            23 => rotated[dim0-1-z][dim1-1-y][dim2-1-x] = self.0[x][y][z],
-                            _ => (),
-                        }
+                        let (a,b,c) = self.transform_position(k, mirrored, x, y, z);
+                        transformed[a][b][c] = self.0[x][y][z];
                     }
                 }
             }
-            Cube(rotated)
+            Cube(transformed)
         }
 
-        fn generate_congruent_shapes(&self) -> Vec<Cube> {
-            let dim0 = self.0.len();
-            let dim1 = self.0[0].len();
-            let dim2 = self.0[0][0].len();
+        fn rotate_k(&self, k: usize) -> Cube {
+            self.transform(k, false)
+        }
 
-            let mut cshapes = Vec::new();
+        // Rotation numbers that map the cuboid onto a cuboid of the same shape.
+        fn rotation_ids(&self) -> Vec<usize> {
+            let (dim0,dim1,dim2) = self.shape();
 
             // x,y,z
-            if true {
-                cshapes.push(self.rotate_k(0));
-                cshapes.push(self.rotate_k(12));
-                cshapes.push(self.rotate_k(13));
-                cshapes.push(self.rotate_k(14));
-            }
+            let mut ids = vec![0, 12, 13, 14];
             // x,z,y
             if dim1 == dim2 {
-                cshapes.push(self.rotate_k(1));
-                cshapes.push(self.rotate_k(2));
-                cshapes.push(self.rotate_k(3));
-                cshapes.push(self.rotate_k(15));
-                
+                ids.extend([1, 2, 3, 15]);
             }
             // y,x,z
             if dim0 == dim1 {
-                cshapes.push(self.rotate_k(4));
-                cshapes.push(self.rotate_k(5));
-                cshapes.push(self.rotate_k(6));
-                cshapes.push(self.rotate_k(16));
+                ids.extend([4, 5, 6, 16]);
             }
             // z,y,x
             if dim0 == dim2 {
-                cshapes.push(self.rotate_k(9));
-                cshapes.push(self.rotate_k(10));
-                cshapes.push(self.rotate_k(11));
-                cshapes.push(self.rotate_k(23));
+                ids.extend([9, 10, 11, 23]);
             }
-
             if dim0 == dim1 && dim0 == dim2 {
                 // y,z,x
-                cshapes.push(self.rotate_k(7));
-                cshapes.push(self.rotate_k(17));
-                cshapes.push(self.rotate_k(18));
-                cshapes.push(self.rotate_k(19));
-
+                ids.extend([7, 17, 18, 19]);
                 // z,x,y
-                cshapes.push(self.rotate_k(8));
-                cshapes.push(self.rotate_k(20));
-                cshapes.push(self.rotate_k(21));
-                cshapes.push(self.rotate_k(22));
+                ids.extend([8, 20, 21, 22]);
+            }
+            ids
+        }
+
+        fn generate_congruent_shapes(&self) -> Vec<Cube> {
+            self.rotation_ids().into_iter().map(|k| self.rotate_k(k)).collect()
+        }
+
+        /// Prints the cube in color, layer by layer from left to right, the pieces
+        /// that touch having different colors from palette::PALETTE and the cells
+        /// holding `hole` as gray dots, smaller than the others as for Board::pprint.
+        pub fn pprint(&self, hole: usize) {
+            let colors = cube_colors(self, &PALETTE, |value| value != hole);
+            let (_,dim1,_) = self.shape();
+            for j in 0..dim1 {
+                let rows: Vec<String> = self.0.iter()
+                    .map(|layer| {
+                        layer[j].iter()
+                            .map(|value| {
+                                let (r,g,b) = colors.get(value).copied().unwrap_or(HOLE_COLOR);
+                                let cell = if *value == hole { "·" } else { "●" };
+                                cell.truecolor(r,g,b).to_string()
+                            })
+                            .collect()
+                    })
+                    .collect();
+                println!("{}", rows.join("  "));
             }
-            cshapes
         }
 
         pub fn normalize_coordinates(&self) -> Vec<(usize,usize,usize)> {
@@ -134,8 +156,35 @@ pub mod cube {
 
     impl Transformable for Cube {
         fn get_all_transformations(&self) -> Vec<Self> {
+            let mut transformations = self.generate_congruent_shapes();
+            transformations.extend(self.rotation_ids().into_iter().map(|k| self.transform(k, true)));
+            transformations
+        }
+
+        fn get_rotations(&self) -> Vec<Self> {
             self.generate_congruent_shapes()
         }
+
+        fn get_symmetries(&self) -> Vec<Vec<usize>> {
+            let (dim0,dim1,dim2) = self.shape();
+            // Mirror images too: they swap each one-sided piece with its twin.
+            self.rotation_ids().into_iter()
+                .flat_map(|k| [(k,false), (k,true)])
+                .filter(|&(k,mirrored)| self.transform(k, mirrored) == *self)
+                .map(|(k,mirrored)| {
+                    let mut permutation = Vec::with_capacity(dim0*dim1*dim2);
+                    for x in 0..dim0 {
+                        for y in 0..dim1 {
+                            for z in 0..dim2 {
+                                let (a,b,c) = self.transform_position(k, mirrored, x, y, z);
+                                permutation.push(a*dim1*dim2 + b*dim2 + c);
+                            }
+                        }
+                    }
+                    permutation
+                })
+                .collect()
+        }
     }
     
     fn conv2usize(cube: Vec<Vec<Vec<bool>>>) -> Vec<Vec<Vec<usize>>> {
@@ -152,20 +201,10 @@ pub mod cube {
             .collect()
     }
     
-    pub fn congruent_figures_for_each_piece_3d(n: usize) -> Vec<Vec<Vec<(usize,usize,usize)>>> {
-        let all_pieces = free_polycubes(n);
-        let mut ret = Vec::new();
-        for piece_b in all_pieces {
-            let piece = conv2usize(piece_b);
-            let mut congruent_figures = Vec::new();
-            for figure in Cube(piece).generate_congruent_shapes() {
-                let normalized_figure = figure.normalize_coordinates();
-                if !congruent_figures.contains(&normalized_figure) {
-                    congruent_figures.push(normalized_figure);
-                }
-            }
-            ret.push(congruent_figures);
-        }
-        ret
+    /// The orientations of every polycube of size n that `orientation` tells
+    /// apart, see PieceSet::orientations.
+    pub fn congruent_figures_for_each_piece_3d(n: usize, orientation: Orientation) -> Vec<Vec<Vec<(usize,usize,usize)>>> {
+        let cubes: Vec<Vec<Vec<Vec<usize>>>> = polycubes(n, orientation).into_iter().map(conv2usize).collect();
+        PieceSet::from_cubes(&cubes).map_or(Vec::new(), |pieces| pieces.with_orientation(orientation).orientations())
     }
 }
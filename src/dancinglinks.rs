@@ -1,63 +1,243 @@
 pub mod dlx {
-    use std::{
-        cmp::Ordering,
-        ops::Range,
-        usize,
-    };
+    use std::ops::ControlFlow;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
 
     pub fn solve(mut m: Matrix, num_solutions: usize) -> Vec<Vec<usize>> {
         let mut answers = Vec::new();
-        let mut answer = Vec::new();
-        go(&mut m, &mut answer, &mut answers, num_solutions);
+        search(&mut m, |answer| {
+            answers.push(answer.to_vec());
+            if num_solutions > 0 && answers.len() >= num_solutions {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
         answers
     }
 
-    fn go(m: &mut Matrix, partial_answer: &mut Vec<usize>, answers: &mut Vec<Vec<usize>>, num_solutions: usize) {
-        if num_solutions > 0 && answers.len() >= num_solutions {
-            return;
-        }
-        let c = {
-            let mut i = m.x.cursor(0);
-            let mut c = match i.next(&m.x) {
-                Some(it) => it,
-                None => {
-                    let mut answer: Vec<usize> = partial_answer.iter().map(|&cell| m.row_of(cell)).collect();
-                    answer.sort();
-                    answers.push(answer);
-                    return;
+    /// Number of exact covers, without collecting them.
+    pub fn count(m: &mut Matrix) -> usize {
+        let mut num_solutions = 0;
+        search(m, |_| {
+            num_solutions += 1;
+            ControlFlow::Continue(())
+        });
+        num_solutions
+    }
+
+    /// Calls `visit` with the (sorted) row indices of every exact cover as soon as
+    /// it is found. Returning `ControlFlow::Break` from `visit` ends the search.
+    /// The matrix is left in its original state either way, so it can be searched again.
+    pub fn search<F>(m: &mut Matrix, mut visit: F)
+    where
+        F: FnMut(&[usize]) -> ControlFlow<()>,
+    {
+        let mut partial_answer = Vec::new();
+        let _ = go(m, &mut partial_answer, None, &mut visit);
+    }
+
+    /// Same as search, with the subtrees below the first few levels of the search
+    /// tree handed out to `num_threads` worker threads. The solutions come back to
+    /// the calling thread, which runs `visit`, in no particular order.
+    pub fn par_search<F>(m: &Matrix, num_threads: usize, mut visit: F)
+    where
+        F: FnMut(&[usize]) -> ControlFlow<()>,
+    {
+        let subproblems = split(&mut m.clone(), num_threads * 16);
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel::<Vec<usize>>();
+        thread::scope(|scope| {
+            for _ in 0..num_threads.min(subproblems.len()) {
+                let sender = sender.clone();
+                let (subproblems, next, stop) = (&subproblems, &next, &stop);
+                scope.spawn(move || {
+                    let mut m = m.clone();
+                    loop {
+                        let k = next.fetch_add(1, Ordering::Relaxed);
+                        if k >= subproblems.len() || stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let mut partial_answer = subproblems[k].clone();
+                        m.select(&partial_answer);
+                        let _ = go(&mut m, &mut partial_answer, Some(stop), &mut |answer| {
+                            match sender.send(answer.to_vec()) {
+                                Ok(()) => ControlFlow::Continue(()),
+                                Err(_) => ControlFlow::Break(()),
+                            }
+                        });
+                        m.unselect(&subproblems[k]);
+                    }
+                });
+            }
+            drop(sender);
+            for answer in receiver.iter() {
+                if visit(&answer).is_break() {
+                    stop.store(true, Ordering::Relaxed);
+                    break;
                 }
-            };
-            while let Some(next_c) = i.next(&m.x) {
-                if m.size[next_c] < m.size[c] {
-                    c = next_c;
+            }
+        });
+    }
+
+    // The column that go branches on: the one with the fewest branches, that is
+    // the rows left to choose from, discounted by the number of rows the column
+    // still needs, plus one for choosing none if it needs none.
+    fn choose_column(m: &Matrix) -> Option<usize> {
+        let degree = |c: usize| (m.size[c] + 1).saturating_sub(m.need(c));
+        let mut i = m.x.cursor(0);
+        let mut c = i.next(&m.x)?;
+        while let Some(next_c) = i.next(&m.x) {
+            if degree(next_c) < degree(c) {
+                c = next_c;
+            }
+        }
+        Some(c)
+    }
+
+    // Walks the search tree level by level until it has at least `min_subproblems`
+    // nodes on one level, or runs out of levels, and returns that level. A node is
+    // the list of cells of the rows chosen on the way down, as in go. Complete
+    // solutions found above that level are kept as they are, and so are nodes
+    // that branch on a column with bounds other than "exactly once", whose
+    // children are not just a list of rows.
+    fn split(m: &mut Matrix, min_subproblems: usize) -> Vec<Vec<usize>> {
+        let mut level: Vec<Vec<usize>> = vec![Vec::new()];
+        while level.len() < min_subproblems {
+            let mut next_level = Vec::new();
+            let mut expanded = false;
+            for partial_answer in &level {
+                m.select(partial_answer);
+                match choose_column(m) {
+                    Some(c) if m.saturates(c) && m.need(c) > 0 => {
+                        expanded = true;
+                        let mut r = m.y.cursor(c);
+                        while let Some(r) = r.next(&m.y) {
+                            let mut child = partial_answer.clone();
+                            child.push(r);
+                            next_level.push(child);
+                        }
+                    }
+                    _ => next_level.push(partial_answer.clone()),
                 }
+                m.unselect(partial_answer);
+            }
+            level = next_level;
+            if !expanded {
+                break;
+            }
+        }
+        level
+    }
+
+    fn go<F>(m: &mut Matrix, partial_answer: &mut Vec<usize>, stop: Option<&AtomicBool>, visit: &mut F)
+             -> ControlFlow<()>
+    where
+        F: FnMut(&[usize]) -> ControlFlow<()>,
+    {
+        if stop.is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+            return ControlFlow::Break(());
+        }
+        let c = match choose_column(m) {
+            Some(c) => c,
+            None => {
+                let mut answer: Vec<usize> = partial_answer.iter().map(|&cell| m.row_of(cell)).collect();
+                answer.sort();
+                return visit(&answer);
             }
-            c
         };
 
-        m.cover(c);
+        if m.size[c] < m.need(c) {
+            return ControlFlow::Continue(());
+        }
+        if !m.saturates(c) {
+            return go_multiple(m, c, partial_answer, stop, visit);
+        }
+
+        let optional = m.need(c) == 0;
+        m.commit(c);
+        let mut flow = ControlFlow::Continue(());
         let mut r = m.y.cursor(c);
         while let Some(r) = r.next(&m.y) {
             partial_answer.push(r);
             let mut j = m.x.cursor(r);
             while let Some(j) = j.next(&m.x) {
-                m.cover(m.c[j]);
+                m.commit(j);
             }
-            go(m, partial_answer, answers, num_solutions);
+            flow = go(m, partial_answer, stop, visit);
             let mut j = m.x.cursor(r);
             while let Some(j) = j.prev(&m.x) {
-                m.uncover(m.c[j]);
+                m.uncommit(j);
             }
             partial_answer.pop();
+            if flow.is_break() {
+                break;
+            }
+        }
+        // A column that already has enough rows can also do without one more.
+        if optional && flow.is_continue() {
+            flow = go(m, partial_answer, stop, visit);
+        }
+        m.uncommit(c);
+        flow
+    }
+
+    // Branches on a column that the next row does not fill up (Knuth's Algorithm
+    // M). Each row is tried in turn and then set aside for the rest of this node,
+    // so the rows the column ends up with are always found in the order of its
+    // list and every solution comes up once. Last, if the column has enough rows,
+    // it is closed without taking any more.
+    fn go_multiple<F>(m: &mut Matrix, c: usize, partial_answer: &mut Vec<usize>, stop: Option<&AtomicBool>,
+                      visit: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(&[usize]) -> ControlFlow<()>,
+    {
+        let mut flow = ControlFlow::Continue(());
+        let mut set_aside = Vec::new();
+        let mut r = m.y.cursor(c);
+        while let Some(r) = r.next(&m.y) {
+            if m.size[c] < m.need(c) {
+                break;
+            }
+            partial_answer.push(r);
+            m.detach(r);
+            m.commit(r);
+            let mut j = m.x.cursor(r);
+            while let Some(j) = j.next(&m.x) {
+                m.commit(j);
+            }
+            flow = go(m, partial_answer, stop, visit);
+            let mut j = m.x.cursor(r);
+            while let Some(j) = j.prev(&m.x) {
+                m.uncommit(j);
+            }
+            m.uncommit(r);
+            partial_answer.pop();
+            set_aside.push(r);
+            if flow.is_break() {
+                break;
+            }
         }
-        m.uncover(c);
+        if m.need(c) == 0 && flow.is_continue() {
+            m.x.remove(c);
+            flow = go(m, partial_answer, stop, visit);
+            m.x.restore(c);
+        }
+        for &r in set_aside.iter().rev() {
+            m.attach(r);
+        }
+        flow
     }
 
+    #[derive(Clone)]
     struct Link {
         prev: usize,
         next: usize,
     }
 
+    #[derive(Clone)]
     struct LinkedList {
         data: Vec<Link>,
     }
@@ -95,7 +275,7 @@ pub mod dlx {
         }
         
         fn cursor(&self, head: usize) -> Cursor {
-            Cursor { head: head, curr: head }
+            Cursor { head, curr: head }
         }
     }
 
@@ -123,9 +303,11 @@ pub mod dlx {
         }
     }
 
+    #[derive(Clone)]
     pub struct Matrix {
-        // Auxilary map to get from cell to row, could be encoded more efficiently.
-        row_ranges: Vec<Range<usize>>,
+        // Auxilary map to get from cell to row. Column headers map to usize::MAX.
+        row: Vec<usize>,
+        num_rows: usize,
 
         // SoA fields
         // Links along the horizontal dimension
@@ -136,25 +318,68 @@ pub mod dlx {
         c: Vec<usize>,
         // For column headers, the size of the column
         size: Vec<usize>,
+        // Color of every cell, 0 for none. Only cells in secondary columns have one.
+        color: Vec<usize>,
+        // For column headers, the cell whose color the column was purified with,
+        // or usize::MAX.
+        purified_by: Vec<usize>,
+        // For column headers, how many rows a solution may have in the column,
+        // and how many the rows chosen so far have.
+        lower: Vec<usize>,
+        upper: Vec<usize>,
+        count: Vec<usize>,
     }
 
     impl Matrix {
+        /// A matrix whose `n_cols` columns are all primary: every solution covers
+        /// each of them exactly once.
         pub fn new(n_cols: usize) -> Matrix {
+            Matrix::with_secondary(n_cols, 0)
+        }
+
+        /// A matrix with `n_primary` primary columns, numbered 0..n_primary, followed
+        /// by `n_secondary` secondary columns. A solution covers every primary column
+        /// exactly once and every secondary column at most once.
+        pub fn with_secondary(n_primary: usize, n_secondary: usize) -> Matrix {
+            let mut bounds = vec![(1,1); n_primary];
+            bounds.resize(n_primary + n_secondary, (0,1));
+            Matrix::with_multiplicities(&bounds)
+        }
+
+        /// A matrix whose column i is covered by at least `bounds[i].0` and at most
+        /// `bounds[i].1` rows of every solution (Knuth's Algorithm M). Columns
+        /// with a lower bound of 0 are secondary.
+        pub fn with_multiplicities(bounds: &[(usize, usize)]) -> Matrix {
+            let n_cols = bounds.len();
             let mut ret = Matrix {
-                row_ranges: Vec::new(),
+                row: Vec::with_capacity(n_cols + 1),
+                num_rows: 0,
                 x: LinkedList::with_capacity(n_cols + 1),
                 y: LinkedList::with_capacity(n_cols + 1),
                 c: Vec::with_capacity(n_cols + 1),
                 size: Vec::with_capacity(n_cols + 1),
+                color: Vec::with_capacity(n_cols + 1),
+                purified_by: Vec::with_capacity(n_cols + 1),
+                lower: Vec::with_capacity(n_cols + 1),
+                upper: Vec::with_capacity(n_cols + 1),
+                count: Vec::with_capacity(n_cols + 1),
             };
             ret.alloc_column();
-            for _ in 0..n_cols {
-                ret.add_column();
+            // Secondary column headers stay out of the header list, so go never
+            // branches on them. Covering one still takes the rows that use it out
+            // of the other columns.
+            for &(lower,upper) in bounds {
+                assert!(lower <= upper && upper > 0, "invalid multiplicity {}..={}", lower, upper);
+                let c = if lower > 0 { ret.add_column() } else { ret.alloc_column() };
+                ret.lower[c] = lower;
+                ret.upper[c] = upper;
             }
             ret
         }
         fn alloc(&mut self, c: usize) -> usize {
             self.c.push(c);
+            self.color.push(0);
+            self.row.push(self.num_rows);
             let cell_idx = self.x.alloc();
             self.y.alloc();
             cell_idx
@@ -162,73 +387,265 @@ pub mod dlx {
         fn alloc_column(&mut self) -> usize {
             let cell_idx = self.alloc(0);
             self.c[cell_idx] = cell_idx;
+            self.row[cell_idx] = usize::MAX;
             self.size.push(0);
+            self.purified_by.push(usize::MAX);
+            self.lower.push(0);
+            self.upper.push(1);
+            self.count.push(0);
             cell_idx
         }
-        fn add_column(&mut self) {
+        fn add_column(&mut self) -> usize {
             let new_col = self.alloc_column();
             self.x.insert(self.x.data[0].prev, new_col);
+            new_col
         }
 
+        /// Adds a row covering the given columns.
         pub fn add_row(&mut self, row: &[usize]) {
-            let row_start = self.x.data.len();
-            let mut c = 0;
-            let mut prev = None;
-            let mut count: usize = 0;
-            for i in 0..self.size.len()-1 {
-                c = self.x.data[c].next;
-                if row.contains(&i) {
-                    self.size[c] += 1;
-                    let new_cell = self.alloc(c);
-                    self.y.insert(self.y.data[c].prev, new_cell);
-                    if let Some(prev) = prev {
-                        self.x.insert(prev, new_cell);
-                    }
-                    prev = Some(new_cell);
+            let colored: Vec<(usize,usize)> = row.iter().map(|&i| (i, 0)).collect();
+            self.add_colored_row(&colored);
+        }
 
-                    count += 1;
-                    if count == row.len() {
-                        break;
-                    }
+        /// Adds a row covering the given (column, color) pairs, where color 0 means
+        /// no color. Only secondary columns can have a color, and any number of rows
+        /// can share a colored column in a solution as long as they all give it the
+        /// same color (Knuth's exact cover with colors, Algorithm C).
+        pub fn add_colored_row(&mut self, row: &[(usize, usize)]) {
+            let mut columns = row.to_vec();
+            columns.sort();
+            columns.dedup_by_key(|&mut (i,_)| i);
+            let mut prev = None;
+            for (i,color) in columns {
+                // The header of column i is the (i+1)-th cell, after the root.
+                let c = i + 1;
+                assert!(c < self.size.len(), "column {} is out of range", i);
+                assert!(color == 0 || (self.lower[c], self.upper[c]) == (0, 1),
+                        "column {} cannot have a color, it is not secondary", i);
+                self.size[c] += 1;
+                let new_cell = self.alloc(c);
+                self.color[new_cell] = color;
+                self.y.insert(self.y.data[c].prev, new_cell);
+                if let Some(prev) = prev {
+                    self.x.insert(prev, new_cell);
                 }
+                prev = Some(new_cell);
             }
-            let row_end = self.x.data.len();
-            self.row_ranges.push(row_start..row_end);
+            self.num_rows += 1;
         }
 
         fn row_of(&self, cell: usize) -> usize {
-            self.row_ranges.binary_search_by(|range| {
-                if cell < range.start {
-                    Ordering::Greater
-                } else if range.start <= cell && cell < range.end {
-                    Ordering::Equal
-                } else {
-                    Ordering::Less
+            self.row[cell]
+        }
+
+        // Number of rows column c still needs.
+        fn need(&self, c: usize) -> usize {
+            self.lower[c].saturating_sub(self.count[c])
+        }
+
+        // True if the next row chosen in column c leaves no room for more.
+        fn saturates(&self, c: usize) -> bool {
+            self.count[c] + 1 == self.upper[c]
+        }
+
+        // Covers the columns of the rows that `cells` belong to, one cell per row, the
+        // way go does when it picks those rows.
+        fn select(&mut self, cells: &[usize]) {
+            for &r in cells {
+                self.commit(self.c[r]);
+                let mut j = self.x.cursor(r);
+                while let Some(j) = j.next(&self.x) {
+                    self.commit(j);
+                }
+            }
+        }
+
+        // Undoes select(cells).
+        fn unselect(&mut self, cells: &[usize]) {
+            for &r in cells.iter().rev() {
+                let mut j = self.x.cursor(r);
+                while let Some(j) = j.prev(&self.x) {
+                    self.uncommit(j);
                 }
-            }).unwrap()
+                self.uncommit(self.c[r]);
+            }
+        }
+
+        // Takes the row of cell i out of every column but i's own.
+        fn hide(&mut self, i: usize) {
+            let mut j = self.x.cursor(i);
+            while let Some(j) = j.next(&self.x) {
+                self.y.remove(j);
+                self.size[self.c[j]] -= 1;
+            }
+        }
+        fn unhide(&mut self, i: usize) {
+            let mut j = self.x.cursor(i);
+            while let Some(j) = j.prev(&self.x) {
+                self.size[self.c[j]] += 1;
+                self.y.restore(j);
+            }
         }
 
-        fn cover(&mut self, c: usize) {
-            self.x.remove(c);
+        // Takes the row of cell i out of every column, its own included.
+        fn detach(&mut self, i: usize) {
+            self.y.remove(i);
+            self.size[self.c[i]] -= 1;
+            self.hide(i);
+        }
+        fn attach(&mut self, i: usize) {
+            self.unhide(i);
+            self.size[self.c[i]] += 1;
+            self.y.restore(i);
+        }
+
+        // Hides the rows that give the column of cell p a color other than p's.
+        fn purify(&mut self, p: usize) {
+            let c = self.c[p];
+            self.purified_by[c] = p;
             let mut i = self.y.cursor(c);
             while let Some(i) = i.next(&self.y) {
-                let mut j = self.x.cursor(i);
-                while let Some(j) = j.next(&self.x) {
-                    self.y.remove(j);
-                    self.size[self.c[j]] -= 1;
+                if self.color[i] != self.color[p] {
+                    self.hide(i);
                 }
             }
         }
-        fn uncover(&mut self, c: usize) {
+        fn unpurify(&mut self, p: usize) {
+            let c = self.c[p];
             let mut i = self.y.cursor(c);
             while let Some(i) = i.prev(&self.y) {
-                let mut j = self.x.cursor(i);
-                while let Some(j) = j.prev(&self.x) {
-                    self.size[self.c[j]] += 1;
-                    self.y.restore(j);
+                if self.color[i] != self.color[p] {
+                    self.unhide(i);
+                }
+            }
+            self.purified_by[c] = usize::MAX;
+        }
+
+        // Counts the row of cell j, which has just been chosen, in j's column (j
+        // can also be the column header itself). A column that is full leaves the
+        // header list and hides its remaining rows. Colored cells purify their
+        // column instead; a column purified by an earlier row already has the
+        // right color.
+        fn commit(&mut self, j: usize) {
+            let c = self.c[j];
+            if self.color[j] != 0 {
+                if self.purified_by[c] == usize::MAX {
+                    self.purify(j);
                 }
+                return;
             }
-            self.x.restore(c);
+            self.count[c] += 1;
+            if self.count[c] == self.upper[c] {
+                if self.lower[c] > 0 {
+                    self.x.remove(c);
+                }
+                let mut i = self.y.cursor(c);
+                while let Some(i) = i.next(&self.y) {
+                    self.hide(i);
+                }
+            }
+        }
+        fn uncommit(&mut self, j: usize) {
+            let c = self.c[j];
+            if self.color[j] != 0 {
+                if self.purified_by[c] == j {
+                    self.unpurify(j);
+                }
+                return;
+            }
+            if self.count[c] == self.upper[c] {
+                let mut i = self.y.cursor(c);
+                while let Some(i) = i.prev(&self.y) {
+                    self.unhide(i);
+                }
+                if self.lower[c] > 0 {
+                    self.x.restore(c);
+                }
+            }
+            self.count[c] -= 1;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn sorted(mut answers: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+            answers.sort();
+            answers
+        }
+
+        // The example of exact covering with colors in TAOCP 7.2.2.1: primary
+        // columns p, q, r and secondary x, y, with colors A and B.
+        fn knuth_xcc() -> Matrix {
+            let (p, q, r, x, y) = (0, 1, 2, 3, 4);
+            let (a, b) = (1, 2);
+            let mut m = Matrix::with_secondary(3, 2);
+            m.add_colored_row(&[(p,0), (q,0), (x,0), (y,a)]);
+            m.add_colored_row(&[(p,0), (r,0), (x,a), (y,0)]);
+            m.add_colored_row(&[(p,0), (x,b)]);
+            m.add_colored_row(&[(q,0), (x,a)]);
+            m.add_colored_row(&[(r,0), (y,b)]);
+            m
+        }
+
+        #[test]
+        fn colored_rows() {
+            assert_eq!(solve(knuth_xcc(), 0), vec![vec![1, 3]]);
+            // Purifying and unpurifying leave the matrix as it was.
+            let mut m = knuth_xcc();
+            assert_eq!(count(&mut m), 1);
+            assert_eq!(count(&mut m), 1);
+            let mut answers = Vec::new();
+            par_search(&knuth_xcc(), 2, |answer| {
+                answers.push(answer.to_vec());
+                ControlFlow::Continue(())
+            });
+            assert_eq!(answers, vec![vec![1, 3]]);
+        }
+
+        #[test]
+        fn colors_must_agree() {
+            // Primary a and b, secondary s.
+            let (a, b, s) = (0, 1, 2);
+            let mut m = Matrix::with_secondary(2, 1);
+            m.add_colored_row(&[(a,0), (s,1)]);
+            m.add_colored_row(&[(b,0), (s,2)]);
+            m.add_colored_row(&[(b,0), (s,1)]);
+            m.add_colored_row(&[(b,0), (s,0)]);
+            m.add_row(&[b]);
+            // Row 0 goes with the row of the same color and with the one that
+            // leaves s alone, not with a different color or an uncolored use of s.
+            assert_eq!(sorted(solve(m, 0)), vec![vec![0, 2], vec![0, 4]]);
+        }
+
+        #[test]
+        fn multiplicities() {
+            // Column 0 takes one or two rows, column 1 exactly one.
+            let make = || {
+                let mut m = Matrix::with_multiplicities(&[(1,2), (1,1)]);
+                m.add_row(&[0]);
+                m.add_row(&[0]);
+                m.add_row(&[0, 1]);
+                m.add_row(&[1]);
+                m
+            };
+            let expected = vec![vec![0, 1, 3], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2]];
+            assert_eq!(sorted(solve(make(), 0)), expected);
+            let mut m = make();
+            assert_eq!(count(&mut m), 6);
+            assert_eq!(count(&mut m), 6);
+
+            // A column of (2,3) and four rows, the last two of which also cover a
+            // secondary column, so that only one of them can be in a solution.
+            let mut m = Matrix::with_multiplicities(&[(2,3), (0,1)]);
+            m.add_row(&[0]);
+            m.add_row(&[0]);
+            m.add_row(&[0, 1]);
+            m.add_row(&[0, 1]);
+            let expected = vec![vec![0, 1], vec![0, 1, 2], vec![0, 1, 3], vec![0, 2], vec![0, 3],
+                                vec![1, 2], vec![1, 3]];
+            assert_eq!(sorted(solve(m, 0)), expected);
         }
     }
 }
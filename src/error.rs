@@ -0,0 +1,55 @@
+pub mod error {
+    use std::fmt;
+
+    #[derive(Clone,Debug,PartialEq,Eq)]
+    pub enum SolverError {
+        /// A piece size of 0, which no piece has.
+        UnsupportedSize(usize),
+        /// The board or cube has no cells at all.
+        EmptyBoard,
+        /// Rows (or layers) of the input have different lengths. `index` locates
+        /// the offending row: `[row]` for boards, `[layer]` or `[layer, row]` for cubes.
+        RaggedBoard { index: Vec<usize>, len: usize, expected: usize },
+        /// The open cells cannot be covered by the pieces because their areas differ.
+        AreaMismatch { open_cells: usize, piece_area: usize },
+        /// A connected region of open cells, starting at `cell`, whose size is
+        /// not a multiple of the piece size (of the greatest common divisor of
+        /// the piece sizes if they differ).
+        RegionSize { cell: Vec<usize>, size: usize, n: usize },
+        /// No board or cube of this name in the test set.
+        UnknownTestName(String),
+        /// A piece count or subset refers to a piece index past the end of the piece list.
+        UnknownPiece { index: usize, num_pieces: usize },
+        /// Piece k of a piece set has no cells.
+        EmptyPiece(usize),
+        /// No piece of this name, or none in the piece set.
+        UnknownPieceName(String),
+    }
+
+    impl fmt::Display for SolverError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                SolverError::UnsupportedSize(n) =>
+                    write!(f, "unsupported piece size {}", n),
+                SolverError::EmptyBoard =>
+                    write!(f, "the board is empty"),
+                SolverError::RaggedBoard { index, len, expected } =>
+                    write!(f, "ragged input: {:?} has length {}, expected {}", index, len, expected),
+                SolverError::AreaMismatch { open_cells, piece_area } =>
+                    write!(f, "{} open cells cannot be covered by pieces of total area {}", open_cells, piece_area),
+                SolverError::RegionSize { cell, size, n } =>
+                    write!(f, "the region at {:?} has {} cells, which is not a multiple of {}", cell, size, n),
+                SolverError::UnknownTestName(name) =>
+                    write!(f, "unknown test name '{}'", name),
+                SolverError::UnknownPiece { index, num_pieces } =>
+                    write!(f, "there is no piece {}, the pieces are numbered 0 to {}", index, num_pieces.saturating_sub(1)),
+                SolverError::EmptyPiece(k) =>
+                    write!(f, "piece {} has no cells", k),
+                SolverError::UnknownPieceName(name) =>
+                    write!(f, "there is no piece named '{}'", name),
+            }
+        }
+    }
+
+    impl std::error::Error for SolverError {}
+}
@@ -0,0 +1,46 @@
+// Each file wraps its contents in a module of the same name.
+#![allow(clippy::module_inception)]
+
+pub mod pieces;
+pub mod dancinglinks;
+pub mod board;
+pub mod backtracking;
+pub mod solutionset;
+pub mod polyominolist;
+pub mod polycubelist;
+pub mod cube;
+pub mod testset;
+pub mod solver;
+pub mod boardfile;
+pub mod error;
+pub mod validate;
+pub mod symmetry;
+pub mod bitmap;
+pub mod pieceset;
+pub mod names;
+pub mod render;
+pub mod svg;
+pub mod palette;
+
+pub use dancinglinks::dlx;
+pub use error::error::SolverError;
+pub use board::board::Board;
+pub use cube::cube::Cube;
+pub use pieceset::pieceset::{PieceSet, PieceSet3d, IntoPieceSet, Orientation};
+pub use solutionset::solutionset::{SolutionSet, SolutionCount};
+pub use backtracking::backtracking::{solve_polyomino_bt, solve_polyomino_bt_with, solve_polyomino_bt_stats,
+                                    count_polyomino_bt, count_polyomino_bt_stats, SearchStats};
+pub use solver::solver::{pieces2positions,
+                         pieces2positions3d,
+                         solution2board,
+                         solution2cube,
+                         solve_polyomino_dlx,
+                         count_polyomino_dlx,
+                         count_polycube_dlx,
+                         solve_polyomino_dlx_with,
+                         solve_polycube_dlx,
+                         solve_polycube_dlx_with,
+                         visit_polyomino_dlx,
+                         visit_polycube_dlx,
+                         SolveOptions,
+                         Pruning};
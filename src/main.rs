@@ -1,211 +1,711 @@
-mod pieces;
-mod dancinglinks;
-mod board;
-mod backtracking;
-mod solutionset;
-mod polyominolist;
-mod polycubelist;
-mod cube;
-mod testset;
-
-use std::time::Instant;
-use pieces::piece::*;
-use dancinglinks::dlx::*;
-use board::board::*;
-use solutionset::solutionset::*;
-use backtracking::backtracking::solve_polyomino_bt;
-use polyominolist::polyominolist::*;
-use cube::cube::*;
-use testset::testset::*;
-
-fn have_common_position(positions_a: &[usize], positions_b: &[usize]) -> bool {
-    positions_b.iter().any(|pos| positions_a.contains(pos))
-}
-
-fn pieces2positions(board: &Vec<Vec<usize>>, n: usize) -> (Vec<Vec<usize>>,Vec<usize>) {
-    let num_pieces: usize = get_num_pieces(n);
-    let mut positions: Vec<Vec<usize>> = Vec::new();
-    let mut kinds: Vec<usize> = Vec::new();
-
-    let board_h: usize = board.len();
-    let board_w: usize = board[0].len();
-    let mut hole_positions = Vec::new();
-    for (i,row) in board.iter().enumerate() {
-        for (j,value) in row.iter().enumerate() {
-            if *value == 1 {
-                hole_positions.push(i*board_w+j);
-            }
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::process;
+use std::time::{Duration, Instant};
+use polyomino::board::board::Board;
+use polyomino::cube::cube::Cube;
+use polyomino::testset::testset::*;
+use polyomino::boardfile::boardfile::*;
+use polyomino::validate::validate::{diagnose_board, diagnose_cube};
+use polyomino::pieceset::pieceset::Cell;
+use polyomino::render::render::{piece_labels, render_board, render_cube, side_by_side};
+use polyomino::svg::svg::{cube_sheet_svg, sheet_svg, SvgOptions};
+use polyomino::*;
+
+const USAGE: &str = "\
+Usage: polyomino <command> [options]
+
+Commands:
+  solve        Solve a puzzle and print its solutions
+  count        Print the number of solutions of a puzzle (ignores --limit)
+  check        Explain why a puzzle cannot be solved, if the quick checks can tell
+  enumerate    List the pieces of size n, numbered as --counts expects
+  render       Print the puzzle itself
+  bench        Time the solver (runs the default problems without a puzzle)
+
+Options:
+  --board NAME     Board from the test set, e.g. 501
+  --cube NAME      Cube from the test set, e.g. 401
+  --file PATH      Puzzle file: '.' for open cells, '#' for holes, one row per
+                   line, cube layers separated by blank lines (with --3d)
+  -n, --size N     Piece size (defaults to the first digit of a test set name),
+                   or several sizes to use all their pieces together, e.g. 1-5
+                   or 4,5
+  --pieces PATH    Pieces file for board puzzles, one grid per piece with '#'
+                   for its cells, separated by blank lines (replaces -n)
+  --orientation O  How pieces may be turned: free, one-sided (no flipping, or
+                   no mirroring for cubes) or fixed (no turning either); also
+                   picks the pieces that enumerate lists and -n stands for
+                   (default: free for boards, one-sided for cubes)
+  --solver S       dlx or bt (default: dlx)
+  --limit K        Stop after K solutions, 0 for all (default: 1 for n >= 6, else 0)
+  --format F       pretty, text or box: piece names with box-drawing outlines,
+                   several solutions side by side (default: pretty)
+  --pruning P      Dead-region pruning of the bt solver: off, cells or regions
+                   (default: off)
+  --threads T      Number of search threads, 0 for one per core (default: 1)
+  --counts LIST    Copies of each piece as index:count pairs, e.g. 3:2,7:1;
+                   unlisted pieces are left out (default: one of each)
+  --select NAMES   Only the pieces of these standard names, in this order, e.g.
+                   F,I,L,P,N for pentominoes or V,L,T,Z,A,B,P for the Soma cube
+                   (with -n 3-4); a trailing size tells L4 from L5
+  --svg PATH       Also draw the solutions on an SVG sheet, cubes in isometric
+                   view with their layers pulled apart
+  --repeat R       Number of runs for bench (default: 1)
+  --3d             Read --file as a cube, and enumerate polycubes instead of
+                   polyominoes
+  --no-symmetry-breaking
+                   Search all symmetric copies of the solutions and drop the
+                   congruent ones afterwards
+  -h, --help       Print this message";
+
+#[derive(Clone,Copy,PartialEq)]
+enum Command {
+    Solve,
+    Count,
+    Check,
+    Enumerate,
+    Render,
+    Bench,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+enum SolverKind {
+    Dlx,
+    Bt,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+enum Format {
+    Pretty,
+    Text,
+    Box,
+}
+
+// Width of the terminal that box format fills with solutions side by side.
+const BOX_WIDTH: usize = 80;
+
+enum Puzzle {
+    Board(String, Vec<Vec<usize>>),
+    Cube(String, Vec<Vec<Vec<usize>>>),
+}
+
+// Where the puzzle comes from, as given on the command line. load_inputs turns
+// it into a Puzzle once all the flags are parsed.
+enum Source {
+    Board(String),
+    Cube(String),
+    File(String),
+}
+
+struct Args {
+    command: Command,
+    source: Option<Source>,
+    puzzle: Option<Puzzle>,
+    sizes: Option<Vec<usize>>,
+    pieces_file: Option<String>,
+    pieces: Option<PieceSet>,
+    orientation: Option<Orientation>,
+    solver: SolverKind,
+    limit: Option<usize>,
+    format: Format,
+    repeat: usize,
+    three_d: bool,
+    no_symmetry_breaking: bool,
+    pruning: Option<Pruning>,
+    threads: Option<usize>,
+    counts: Option<HashMap<usize, usize>>,
+    select: Option<Vec<String>>,
+    svg: Option<String>,
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, String> {
+    value.parse::<usize>().map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+fn parse_sizes(flag: &str, value: &str) -> Result<Vec<usize>, String> {
+    let mut sizes = Vec::new();
+    for part in value.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => sizes.extend(parse_number(flag, first)?..=parse_number(flag, last)?),
+            None => sizes.push(parse_number(flag, part)?),
         }
     }
-    for (k,congruent_figures) in congruent_figures_for_each_piece(n).iter().enumerate() {
-        for figure in congruent_figures {
-            let (figure_h,figure_w) = figure.iter().fold((usize::MIN,usize::MIN),
-                                                         |(max_i,max_j),&(i,j)| (max_i.max(i),max_j.max(j)));
-            for offset_i in 0..board_h.saturating_sub(figure_h) {
-                for offset_j in 0..board_w.saturating_sub(figure_w) {
-                    let mut figure_positions: Vec<_> = figure.into_iter()
-                        .map(|(i,j)| (i+offset_i)*board_w + (j+offset_j))
-                        .collect();
-                    if have_common_position(&figure_positions, &hole_positions) {
-                        continue;
-                    } else {
-                        figure_positions.push(k + board_h*board_w);
-                        positions.push(figure_positions);
-                        kinds.push(k);
-                    }
-                }
-            }
+    Ok(sizes)
+}
+
+fn parse_counts(value: &str) -> Result<HashMap<usize, usize>, String> {
+    value.split(',')
+        .map(|pair| {
+            let (index, count) = pair.split_once(':')
+                .ok_or_else(|| format!("--counts expects index:count pairs, got '{}'", pair))?;
+            Ok((parse_number("--counts", index)?, parse_number("--counts", count)?))
+        })
+        .collect()
+}
+
+// A cube if `three_d`, else a board, whatever the number of layers in the file:
+// a cube may well have a single layer.
+fn read_puzzle_file(path: &str, three_d: bool) -> Result<Puzzle, String> {
+    if three_d {
+        let layers = read_cube_file(path).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(Puzzle::Cube(path.to_string(), layers))
+    } else {
+        let mut layers = read_cube_file(path).map_err(|e| format!("{}: {}", path, e))?;
+        if layers.len() > 1 {
+            return Err(format!("{}: found {} layers, use --3d to read a cube", path, layers.len()));
         }
+        Ok(Puzzle::Board(path.to_string(), layers.remove(0)))
     }
+}
 
-    hole_positions.push(board_h*board_w + num_pieces);
-    positions.push(hole_positions);
-    kinds.push(num_pieces);
+fn read_pieces(path: &str) -> Result<PieceSet, String> {
+    let grids = read_pieces_file(path).map_err(|e| format!("{}: {}", path, e))?;
+    PieceSet::from_boards(&grids).map_err(|e| format!("{}: {}", path, e))
+}
 
-    (positions,kinds)
+fn name_to_size(name: &str) -> Option<usize> {
+    name.chars().next().and_then(|c| c.to_digit(10)).map(|d| d as usize)
 }
 
-fn solution2board(solution: &Vec<usize>,
-                  kinds: &Vec<usize>,
-                  positions: &Vec<Vec<usize>>,
-                  board: &Vec<Vec<usize>>) -> Board {
-    let mut ret: Board = Board::new(board.len(), board[0].len());
-    for &k in solution.iter() {
-        let kind_value = kinds[k];
-        for pos in &positions[k] {
-            if *pos >= ret.height()*ret.width() {
-                continue;
-            }
-            let a = pos / ret.width();
-            let b = pos % ret.width();
-            *ret.get_mut(a,b) = kind_value;
+fn parse_args(argv: &[String]) -> Result<Args, String> {
+    let mut iter = argv.iter();
+    let command = match iter.next().map(|s| s.as_str()) {
+        Some("solve") => Command::Solve,
+        Some("count") => Command::Count,
+        Some("check") => Command::Check,
+        Some("enumerate") => Command::Enumerate,
+        Some("render") => Command::Render,
+        Some("bench") => Command::Bench,
+        Some("-h") | Some("--help") | None => {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+    };
+
+    let mut args = Args {
+        command,
+        source: None,
+        puzzle: None,
+        sizes: None,
+        pieces_file: None,
+        pieces: None,
+        orientation: None,
+        solver: SolverKind::Dlx,
+        limit: None,
+        format: Format::Pretty,
+        repeat: 1,
+        three_d: false,
+        no_symmetry_breaking: false,
+        pruning: None,
+        threads: None,
+        counts: None,
+        select: None,
+        svg: None,
+    };
+    while let Some(flag) = iter.next() {
+        if flag == "-h" || flag == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
         }
+        if flag == "--3d" {
+            args.three_d = true;
+            continue;
+        }
+        if flag == "--no-symmetry-breaking" {
+            args.no_symmetry_breaking = true;
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| format!("{} expects a value", flag))?;
+        match flag.as_str() {
+            "--board" => args.source = Some(Source::Board(value.clone())),
+            "--cube" => args.source = Some(Source::Cube(value.clone())),
+            "--file" => args.source = Some(Source::File(value.clone())),
+            "-n" | "--size" => args.sizes = Some(parse_sizes(flag, value)?),
+            "--pieces" => args.pieces_file = Some(value.clone()),
+            "--orientation" => args.orientation = Some(match value.as_str() {
+                "free" => Orientation::Free,
+                "one-sided" => Orientation::OneSided,
+                "fixed" => Orientation::Fixed,
+                _ => return Err(format!("unknown orientation '{}'", value)),
+            }),
+            "--solver" => args.solver = match value.as_str() {
+                "dlx" => SolverKind::Dlx,
+                "bt" => SolverKind::Bt,
+                _ => return Err(format!("unknown solver '{}'", value)),
+            },
+            "--limit" => args.limit = Some(parse_number(flag, value)?),
+            "--format" => args.format = match value.as_str() {
+                "pretty" => Format::Pretty,
+                "text" => Format::Text,
+                "box" => Format::Box,
+                _ => return Err(format!("unknown format '{}'", value)),
+            },
+            "--pruning" => args.pruning = Some(match value.as_str() {
+                "off" => Pruning::Off,
+                "cells" => Pruning::IsolatedCells,
+                "regions" => Pruning::Regions,
+                _ => return Err(format!("unknown pruning '{}'", value)),
+            }),
+            "--threads" => args.threads = Some(parse_number(flag, value)?),
+            "--counts" => args.counts = Some(parse_counts(value)?),
+            "--select" => args.select = Some(value.split(',').map(|name| name.trim().to_string()).collect()),
+            "--svg" => args.svg = Some(value.clone()),
+            "--repeat" => args.repeat = parse_number(flag, value)?.max(1),
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+    Ok(args)
+}
+
+// Loads the puzzle and the pieces that the flags name. What goes wrong here is
+// a missing file or an unknown name rather than a bad command line.
+fn load_inputs(args: &mut Args) -> Result<(), String> {
+    args.puzzle = match &args.source {
+        Some(Source::Board(name)) => Some(Puzzle::Board(name.clone(), lookup_board(name)?)),
+        Some(Source::Cube(name)) => Some(Puzzle::Cube(name.clone(), lookup_cube(name)?)),
+        // A cube if --3d is given, which may come after --file.
+        Some(Source::File(path)) => Some(read_puzzle_file(path, args.three_d)?),
+        None => None,
+    };
+    if let Some(path) = &args.pieces_file {
+        args.pieces = Some(read_pieces(path)?);
+    }
+    Ok(())
+}
+
+fn lookup_board(name: &str) -> Result<Vec<Vec<usize>>, String> {
+    test_board(name).map_err(|e| e.to_string())
+}
+
+fn lookup_cube(name: &str) -> Result<Vec<Vec<Vec<usize>>>, String> {
+    test_cube(name).map_err(|e| e.to_string())
+}
+
+fn piece_sizes(args: &Args) -> Result<Vec<usize>, String> {
+    if let Some(sizes) = &args.sizes {
+        return Ok(sizes.clone());
+    }
+    match &args.puzzle {
+        Some(Puzzle::Board(name, _)) | Some(Puzzle::Cube(name, _)) => name_to_size(name).map(|n| vec![n]),
+        None => None,
+    }.ok_or_else(|| "the piece size is required, use -n".to_string())
+}
+
+// The pieces called by --select, in that order, or all of them.
+fn select_pieces<C: Cell>(pieces: PieceSet<C>, args: &Args) -> Result<PieceSet<C>, String> {
+    match &args.select {
+        Some(names) => {
+            let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+            pieces.select(&names).map_err(|e| e.to_string())
+        }
+        None => Ok(pieces),
+    }
+}
+
+fn polyominoes(args: &Args) -> Result<PieceSet, String> {
+    let pieces = match &args.pieces {
+        Some(pieces) => pieces.clone().with_orientation(args.orientation.unwrap_or(Orientation::Free)),
+        None => PieceSet::all_polyominoes(&piece_sizes(args)?, args.orientation.unwrap_or(Orientation::Free))
+            .map_err(|e| e.to_string())?,
+    };
+    select_pieces(pieces, args)
+}
+
+fn polycubes(args: &Args) -> Result<PieceSet3d, String> {
+    if args.pieces.is_some() {
+        return Err("--pieces only applies to boards".to_string());
+    }
+    let orientation = args.orientation.unwrap_or(Orientation::OneSided);
+    select_pieces(PieceSet::all_polycubes(&piece_sizes(args)?, orientation).map_err(|e| e.to_string())?, args)
+}
+
+// The labels of the values in a solution: the piece_labels of the pieces,
+// once for each of their copies.
+fn solution_labels<C: Cell>(pieces: &PieceSet<C>, args: &Args) -> Result<Vec<String>, String> {
+    let labels = piece_labels(pieces);
+    let kinds = options(args, 0).instance_kinds(pieces.len()).map_err(|e| e.to_string())?;
+    Ok(kinds.iter().map(|&k| labels[k].clone()).collect())
+}
+
+fn options(args: &Args, n: usize) -> SolveOptions {
+    let mut options = SolveOptions::for_size(n);
+    if let Some(limit) = args.limit {
+        options.num_solutions = limit;
+    }
+    options.symmetry_breaking = !args.no_symmetry_breaking;
+    if let Some(pruning) = args.pruning {
+        options.pruning = pruning;
+    }
+    if let Some(threads) = args.threads {
+        options.num_threads = threads;
+    }
+    options.piece_counts = args.counts.clone();
+    options
+}
+
+fn print_grid(grid: &[Vec<usize>]) {
+    let digits = grid.iter().flatten().max().map_or(1, |m| m.to_string().len());
+    for row in grid {
+        let cells: Vec<String> = row.iter().map(|v| format!("{:>w$}", v, w = digits)).collect();
+        println!("{}", cells.join(" "));
+    }
+}
+
+// `labels` names the values of the pieces, and the next value is the one of the
+// holes, as in a solution with labels.len() copies of the pieces.
+fn print_board(board: &Board, labels: &[String], format: Format) {
+    match format {
+        Format::Pretty => board.pprint(labels.len()),
+        Format::Text => print_grid(&board.0),
+        Format::Box => print!("{}", render_board(board, |value| labels.get(value).cloned())),
     }
-    ret
 }
 
-fn solve_polyomino_dlx(board: &Vec<Vec<usize>>, n: usize) -> Vec<Board> {
-    let num_pieces: usize = get_num_pieces(n);
-    let (positions,kinds) = pieces2positions(&board, n);
-    let num_cells = board.iter().map(|row| row.len()).sum::<usize>();
-    let mut m = Matrix::new(num_cells + num_pieces + 1);
-    for pos_1hvec in &positions {
-        m.add_row(&pos_1hvec);
+// Boards in box format, printed side by side as they come, as many at a time as
+// fit in BOX_WIDTH columns.
+struct Gallery {
+    blocks: Vec<String>,
+    used: usize,
+}
+
+impl Gallery {
+    fn new() -> Self {
+        Gallery { blocks: Vec::new(), used: 0 }
+    }
+
+    fn push(&mut self, block: String) {
+        let width = block.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        if !self.blocks.is_empty() && self.used + 2 + width > BOX_WIDTH {
+            self.flush();
+        }
+        self.used += if self.blocks.is_empty() { width } else { 2 + width };
+        self.blocks.push(block);
     }
 
-    let mut solutions = SolutionSet::new();
-    let num_solutions: usize = if n >= 6 { 1 } else { 0 };
-    for solution in solve(m, num_solutions).iter() {
-        let solved_board = solution2board(&solution, &kinds, &positions, &board);
-        solutions.add_solution(solved_board);
+    fn flush(&mut self) {
+        if !self.blocks.is_empty() {
+            println!("{}", side_by_side(&self.blocks, 2, BOX_WIDTH));
+            self.blocks.clear();
+            self.used = 0;
+        }
     }
-    solutions.get_solutions()
 }
 
-fn pieces2positions3d(cube: &Vec<Vec<Vec<usize>>>, n: usize) -> (Vec<Vec<usize>>,Vec<usize>) {
-    let dim0 = cube.len();
-    let dim1 = cube[0].len();
-    let dim2 = cube[0][0].len();
+// Prints solution k of a board puzzle, labelling its pieces with `labels`.
+fn show_solution(k: usize, board: &Board, labels: &[String], format: Format, gallery: &mut Gallery) {
+    if format == Format::Box {
+        gallery.push(format!("solution {}\n{}", k, render_board(board, |value| labels.get(value).cloned())));
+    } else {
+        println!("solution {}", k);
+        print_board(board, labels, format);
+        println!();
+    }
+}
 
-    let num_pieces: usize = get_num_pieces_3d(n);
-    let mut positions: Vec<Vec<usize>> = Vec::new();
-    let mut kinds: Vec<usize> = Vec::new();
-    let mut hole_positions = Vec::new();
-    for i in 0..dim0 {
-        for j in 0..dim1 {
-            for k in 0..dim2 {
-                if cube[i][j][k] == 1 {
-                    hole_positions.push(i*dim1*dim2 + j*dim2 + k);
+// Prints `cube` layer by layer in the given format, `labels` as for print_board.
+fn print_cube(cube: &Cube, labels: &[String], format: Format) {
+    match format {
+        Format::Pretty => cube.pprint(labels.len()),
+        Format::Text => {
+            for (i,plane) in cube.0.iter().enumerate() {
+                if i > 0 {
+                    println!();
                 }
+                print_grid(plane);
             }
         }
+        Format::Box => print!("{}", render_cube(cube, |value| labels.get(value).cloned(), BOX_WIDTH)),
     }
-    for (kind,congruent_figures) in congruent_figures_for_each_piece_3d(n).iter().enumerate() {
-        for figure in congruent_figures {
-            let (figure_dim0,figure_dim1,figure_dim2) = figure.iter()
-                .fold((usize::MIN,usize::MIN,usize::MIN),
-                      |(max_i,max_j,max_k),&(i,j,k)| (max_i.max(i),max_j.max(j),max_k.max(k)));
-            for offset_i in 0..dim0.saturating_sub(figure_dim0) {
-                for offset_j in 0..dim1.saturating_sub(figure_dim1) {
-                    for offset_k in 0..dim2.saturating_sub(figure_dim2) {
-                        let mut figure_positions: Vec<_> = figure.into_iter()
-                            .map(|(i,j,k)| (i+offset_i)*dim1*dim2 + (j+offset_j)*dim2 + (k+offset_k))
-                            .collect();
-                        if have_common_position(&figure_positions, &hole_positions) {
-                            continue;
-                        } else {
-                            figure_positions.push(kind + dim0*dim1*dim2);
-                            positions.push(figure_positions);
-                            kinds.push(kind);
-                        }
-                    }
+}
+
+enum Solutions {
+    Boards(Vec<Board>),
+    Cubes(Vec<Cube>),
+}
+
+impl Solutions {
+    fn len(&self) -> usize {
+        match self {
+            Solutions::Boards(boards) => boards.len(),
+            Solutions::Cubes(cubes) => cubes.len(),
+        }
+    }
+}
+
+fn run_solver(puzzle: &Puzzle, args: &Args) -> Result<Solutions, String> {
+    let solutions = match (puzzle, args.solver) {
+        (Puzzle::Board(_, board), SolverKind::Dlx) => {
+            let pieces = polyominoes(args)?;
+            solve_polyomino_dlx_with(board, &pieces, &options(args, pieces.max_size())).map(Solutions::Boards)
+        }
+        (Puzzle::Board(_, board), SolverKind::Bt) => {
+            let pieces = polyominoes(args)?;
+            solve_polyomino_bt_with(board, &pieces, &options(args, pieces.max_size())).map(Solutions::Boards)
+        }
+        (Puzzle::Cube(_, cube), SolverKind::Dlx) => {
+            let pieces = polycubes(args)?;
+            solve_polycube_dlx_with(cube, &pieces, &options(args, pieces.max_size())).map(Solutions::Cubes)
+        }
+        (Puzzle::Cube(_, _), SolverKind::Bt) => return Err("the backtracking solver only handles boards".to_string()),
+    };
+    solutions.map_err(|e| e.to_string())
+}
+
+fn require_puzzle(args: &Args) -> Result<&Puzzle, String> {
+    args.puzzle.as_ref().ok_or_else(|| "a puzzle is required, use --board, --cube or --file".to_string())
+}
+
+fn cmd_solve(args: &Args) -> Result<(), String> {
+    let puzzle = require_puzzle(args)?;
+    let mut k = 0;
+    let mut gallery = Gallery::new();
+    // The solutions to draw with --svg.
+    let mut sheet = Vec::new();
+    let mut cube_sheet = Vec::new();
+    let result = match (puzzle, args.solver) {
+        (Puzzle::Board(_, board), SolverKind::Dlx) => {
+            let pieces = polyominoes(args)?;
+            let labels = solution_labels(&pieces, args)?;
+            visit_polyomino_dlx(board, &pieces, &options(args, pieces.max_size()), |solution| {
+                k += 1;
+                show_solution(k, solution, &labels, args.format, &mut gallery);
+                if args.svg.is_some() {
+                    sheet.push(solution.clone());
+                }
+                ControlFlow::Continue(())
+            })
+        }
+        (Puzzle::Cube(_, cube), SolverKind::Dlx) => {
+            let pieces = polycubes(args)?;
+            let labels = solution_labels(&pieces, args)?;
+            visit_polycube_dlx(cube, &pieces, &options(args, pieces.max_size()), |solution| {
+                k += 1;
+                println!("solution {}", k);
+                print_cube(solution, &labels, args.format);
+                println!();
+                if args.svg.is_some() {
+                    cube_sheet.push(solution.clone());
+                }
+                ControlFlow::Continue(())
+            })
+        }
+        _ => {
+            if let Solutions::Boards(boards) = run_solver(puzzle, args)? {
+                let labels = solution_labels(&polyominoes(args)?, args)?;
+                for (k,board) in boards.iter().enumerate() {
+                    show_solution(k + 1, board, &labels, args.format, &mut gallery);
                 }
+                if args.svg.is_some() {
+                    sheet = boards;
+                }
+            }
+            Ok(())
+        }
+    };
+    gallery.flush();
+    result.map_err(|e| e.to_string())?;
+    if let Some(path) = &args.svg {
+        let image = match puzzle {
+            Puzzle::Board(_, _) => {
+                let labels = solution_labels(&polyominoes(args)?, args)?;
+                sheet_svg(&sheet, |value| labels.get(value).cloned(), &SvgOptions::default())
+            }
+            Puzzle::Cube(_, _) => {
+                let labels = solution_labels(&polycubes(args)?, args)?;
+                cube_sheet_svg(&cube_sheet, |value| labels.get(value).cloned(), &SvgOptions::default())
             }
+        };
+        std::fs::write(path, image).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+fn cmd_count(args: &Args) -> Result<(), String> {
+    let puzzle = require_puzzle(args)?;
+    let count = match (puzzle, args.solver) {
+        (Puzzle::Board(_, board), SolverKind::Dlx) => count_polyomino_dlx(board, polyominoes(args)?, &options(args, 0)),
+        (Puzzle::Board(_, board), SolverKind::Bt) => count_polyomino_bt(board, polyominoes(args)?, &options(args, 0)),
+        (Puzzle::Cube(_, cube), SolverKind::Dlx) => count_polycube_dlx(cube, polycubes(args)?, &options(args, 0)),
+        (Puzzle::Cube(_, _), SolverKind::Bt) => return Err("the backtracking solver only handles boards".to_string()),
+    }.map_err(|e| e.to_string())?;
+    println!("{} ({} including symmetric copies)", count.distinct, count.raw);
+    Ok(())
+}
+
+fn cmd_check(args: &Args) -> Result<(), String> {
+    let puzzle = require_puzzle(args)?;
+    let problems = match puzzle {
+        Puzzle::Board(_, board) => {
+            let pieces = polyominoes(args)?;
+            let copies = options(args, 0).copies(pieces.len()).map_err(|e| e.to_string())?;
+            diagnose_board(board, pieces.area(&copies), pieces.region_unit(&copies))
+        }
+        Puzzle::Cube(_, cube) => {
+            let pieces = polycubes(args)?;
+            let copies = options(args, 0).copies(pieces.len()).map_err(|e| e.to_string())?;
+            diagnose_cube(cube, pieces.area(&copies), pieces.region_unit(&copies))
         }
+    };
+    if problems.is_empty() {
+        println!("no problems found");
     }
-    hole_positions.push(dim0*dim1*dim2 + num_pieces);
-    positions.push(hole_positions);
-    kinds.push(num_pieces);
+    for problem in problems {
+        println!("{}", problem);
+    }
+    Ok(())
+}
 
-    (positions,kinds)
+fn piece_header(k: usize, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("piece {} ({})", k, name),
+        None => format!("piece {}", k),
+    }
 }
 
-fn solution2cube(solution: &Vec<usize>,
-                 kinds: &Vec<usize>,
-                 positions: &Vec<Vec<usize>>,
-                 cube: &Vec<Vec<Vec<usize>>>) -> Cube {
-    let dim0 = cube.len();
-    let dim1 = cube[0].len();
-    let dim2 = cube[0][0].len();
-    let mut ret: Cube = Cube::new(dim0,dim1,dim2);
-    for &k in solution.iter() {
-        let kind_value = kinds[k];
-        for pos in &positions[k] {
-            if *pos >= dim0*dim1*dim2 {
-                continue;
+fn cmd_enumerate(args: &Args) -> Result<(), String> {
+    if args.three_d {
+        let pieces = polycubes(args)?;
+        for (k,cells) in pieces.pieces().iter().enumerate() {
+            println!("{}", piece_header(k, pieces.name(k)));
+            let (h,w,d) = cells.iter().fold((0,0,0), |(h,w,d),&(i,j,l)| (h.max(i+1), w.max(j+1), d.max(l+1)));
+            let mut cube = Cube::new(h, w, d);
+            for &(i,j,l) in cells {
+                *cube.get_mut(i,j,l) = 1;
+            }
+            if args.format == Format::Box {
+                let label = pieces.name(k).map_or_else(|| k.to_string(), |name| name.to_string());
+                print!("{}", render_cube(&cube, |value| (value == 1).then(|| label.clone()), BOX_WIDTH));
+            } else {
+                // No labels, so the cells that hold 0 print as holes.
+                print_cube(&cube, &[], args.format);
             }
-            let a = pos / (dim1*dim2);
-            let remainder = pos % (dim1*dim2);
-            let b = remainder / dim2;
-            let c = remainder % dim2;
-            *ret.get_mut(a,b,c) = kind_value;
+            println!();
         }
+    } else {
+        let pieces = polyominoes(args)?;
+        let mut gallery = Gallery::new();
+        for (k,cells) in pieces.pieces().iter().enumerate() {
+            let (h,w) = cells.iter().fold((0,0), |(h,w),&(i,j)| (h.max(i+1), w.max(j+1)));
+            let mut board = Board::new(h, w);
+            for &(i,j) in cells {
+                *board.get_mut(i,j) = 1;
+            }
+            if args.format == Format::Box {
+                let label = pieces.name(k).map_or_else(|| k.to_string(), |name| name.to_string());
+                let drawing = render_board(&board, |value| (value == 1).then(|| label.clone()));
+                gallery.push(format!("{}\n{}", piece_header(k, pieces.name(k)), drawing));
+            } else {
+                println!("{}", piece_header(k, pieces.name(k)));
+                // No labels, so the cells that hold 0 print as holes.
+                print_board(&board, &[], args.format);
+                println!();
+            }
+        }
+        gallery.flush();
     }
-    ret
+    Ok(())
 }
 
-fn solve_polycube_dlx(cube: &Vec<Vec<Vec<usize>>>, n: usize) -> Vec<Cube> {
-    let num_pieces: usize = get_num_pieces_3d(n);
-    let (positions,kinds) = pieces2positions3d(&cube, n);
-    let num_cells = cube.len() * cube[0].len() * cube[0][0].len();
-    let mut m = Matrix::new(num_cells + num_pieces + 1);
-    for pos_1hvec in &positions {
-        m.add_row(&pos_1hvec);
+fn cmd_render(args: &Args) -> Result<(), String> {
+    match (require_puzzle(args)?, args.format) {
+        (Puzzle::Board(_, board), Format::Pretty) => Board(board.clone()).pprint(1),
+        (Puzzle::Board(_, board), Format::Text) => print!("{}", write_board(board)),
+        // One outline around the open cells, holes left blank.
+        (Puzzle::Board(_, board), Format::Box) =>
+            print!("{}", render_board(&Board(board.clone()), |value| (value == 0).then(String::new))),
+        (Puzzle::Cube(_, cube), Format::Pretty) => Cube(cube.clone()).pprint(1),
+        (Puzzle::Cube(_, cube), Format::Text) => print!("{}", write_cube(cube)),
+        (Puzzle::Cube(_, cube), Format::Box) =>
+            print!("{}", render_cube(&Cube(cube.clone()), |value| (value == 0).then(String::new), BOX_WIDTH)),
     }
+    Ok(())
+}
 
-    let mut solutions = SolutionSet::new();
-    let num_solutions: usize = 0;
-    for solution in solve(m, num_solutions).iter() {
-        let solved_cube = solution2cube(&solution, &kinds, &positions, &cube);
-        solutions.add_solution(solved_cube);
+fn solver_name(puzzle: &Puzzle, solver: SolverKind) -> &'static str {
+    match (puzzle, solver) {
+        (Puzzle::Board(_, _), SolverKind::Bt) => "Backtracking",
+        _ => "DancingLinks",
     }
-    solutions.get_solutions()
 }
 
-fn main() {
-    let start = Instant::now();
-    let solutions = solve_polycube_dlx(&test_cube("401"), 4);
-    let duration = start.elapsed();
-    println!("DancingLinks: problem=CUBE_401 # of solutions={} elapsed time={:?}", solutions.len(), duration);
+fn problem_name(puzzle: &Puzzle) -> String {
+    match puzzle {
+        Puzzle::Board(name, _) => format!("BOARD_{}", name),
+        Puzzle::Cube(name, _) => format!("CUBE_{}", name),
+    }
+}
 
-    let start = Instant::now();
-    let solutions = solve_polyomino_bt(&test_board("501"), 5);
-    let duration = start.elapsed();
-    println!("Backtracking: problem=BOARD_501 # of solutions={} elapsed time={:?}", solutions.len(), duration);
+fn bench_one(puzzle: &Puzzle, args: &Args) -> Result<(), String> {
+    let mut times: Vec<Duration> = Vec::new();
+    let mut num_solutions = 0;
+    let mut stats = None;
+    for _ in 0..args.repeat {
+        let start = Instant::now();
+        num_solutions = match (puzzle, args.solver) {
+            (Puzzle::Board(_, board), SolverKind::Bt) => {
+                let pieces = polyominoes(args)?;
+                let options = options(args, pieces.max_size());
+                let (solutions, search_stats) = solve_polyomino_bt_stats(board, &pieces, &options)
+                    .map_err(|e| e.to_string())?;
+                stats = Some(search_stats);
+                solutions.len()
+            }
+            _ => run_solver(puzzle, args)?.len(),
+        };
+        times.push(start.elapsed());
+    }
+    let best = times.iter().min().copied().unwrap_or_default();
+    let mean = times.iter().sum::<Duration>() / times.len() as u32;
+    println!("{}: problem={} # of solutions={} elapsed time={:?} (mean {:?} over {} runs)",
+             solver_name(puzzle, args.solver), problem_name(puzzle), num_solutions, best, mean, times.len());
+    if let Some(stats) = stats {
+        println!("    nodes={} pruned={}", stats.nodes, stats.pruned);
+    }
+    Ok(())
+}
 
-    let start = Instant::now();
-    let solutions = solve_polyomino_dlx(&test_board("401"), 4);
-    let duration = start.elapsed();
-    println!("DancingLinks: problem=BOARD_601 # of solutions={} elapsed time={:?}", solutions.len(), duration);
+fn cmd_bench(args: &Args) -> Result<(), String> {
+    if let Some(puzzle) = &args.puzzle {
+        return bench_one(puzzle, args);
+    }
+    let defaults = [
+        (Puzzle::Cube("401".to_string(), lookup_cube("401")?), SolverKind::Dlx),
+        (Puzzle::Board("501".to_string(), lookup_board("501")?), SolverKind::Bt),
+        (Puzzle::Board("401".to_string(), lookup_board("401")?), SolverKind::Dlx),
+    ];
+    for (puzzle, solver) in defaults {
+        let n = match &puzzle {
+            Puzzle::Board(name, _) | Puzzle::Cube(name, _) => name_to_size(name).unwrap_or(0),
+        };
+        let args = Args { solver, source: None, puzzle: None, sizes: Some(vec![n]), pieces_file: None, pieces: None,
+                          counts: None, select: None, svg: None, ..*args };
+        bench_one(&puzzle, &args)?;
+    }
+    Ok(())
+}
 
+fn main() {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = match parse_args(&argv) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let result = load_inputs(&mut args).and_then(|()| match args.command {
+        Command::Solve => cmd_solve(&args),
+        Command::Count => cmd_count(&args),
+        Command::Check => cmd_check(&args),
+        Command::Enumerate => cmd_enumerate(&args),
+        Command::Render => cmd_render(&args),
+        Command::Bench => cmd_bench(&args),
+    });
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
 }
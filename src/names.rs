@@ -0,0 +1,188 @@
+pub mod names {
+    use crate::pieceset::pieceset::{Cell, Orientation};
+
+    // A named piece: its name, the name of its mirror image, which is the same
+    // name for pieces that are their own mirror image, and its cells.
+    type Named<C> = (&'static str, &'static str, &'static [C]);
+
+    /// The tetrominoes, with the one-sided names of Tetris: J and Z are the
+    /// mirror images of L and S.
+    const TETROMINOES: [Named<(usize,usize)>; 5] = [
+        ("I", "I", &[(0,0), (1,0), (2,0), (3,0)]),
+        ("O", "O", &[(0,0), (0,1), (1,0), (1,1)]),
+        ("T", "T", &[(0,0), (0,1), (0,2), (1,1)]),
+        ("L", "J", &[(0,0), (1,0), (2,0), (2,1)]),
+        ("S", "Z", &[(0,1), (0,2), (1,0), (1,1)]),
+    ];
+
+    /// The pentominoes with Conway's and Golomb's letters, the mirror image of a
+    /// piece taking a prime.
+    const PENTOMINOES: [Named<(usize,usize)>; 12] = [
+        ("F", "F'", &[(0,1), (0,2), (1,0), (1,1), (2,1)]),
+        ("I", "I", &[(0,0), (1,0), (2,0), (3,0), (4,0)]),
+        ("L", "L'", &[(0,0), (1,0), (2,0), (3,0), (3,1)]),
+        ("N", "N'", &[(0,1), (1,1), (2,0), (2,1), (3,0)]),
+        ("P", "P'", &[(0,0), (0,1), (1,0), (1,1), (2,0)]),
+        ("T", "T", &[(0,0), (0,1), (0,2), (1,1), (2,1)]),
+        ("U", "U", &[(0,0), (0,2), (1,0), (1,1), (1,2)]),
+        ("V", "V", &[(0,0), (1,0), (2,0), (2,1), (2,2)]),
+        ("W", "W", &[(0,0), (1,0), (1,1), (2,1), (2,2)]),
+        ("X", "X", &[(0,1), (1,0), (1,1), (1,2), (2,1)]),
+        ("Y", "Y'", &[(0,1), (1,0), (1,1), (2,1), (3,1)]),
+        ("Z", "Z'", &[(0,0), (0,1), (1,1), (2,1), (2,2)]),
+    ];
+
+    /// The V tricube and the tetracubes, with the names of the Soma pieces (V, L,
+    /// T, Z, A, B and P, the screws A and B being mirror images of each other)
+    /// and I and O for the other two.
+    const POLYCUBES: [Named<(usize,usize,usize)>; 8] = [
+        ("V", "V", &[(0,0,0), (0,0,1), (0,1,0)]),
+        ("I", "I", &[(0,0,0), (0,0,1), (0,0,2), (0,0,3)]),
+        ("O", "O", &[(0,0,0), (0,0,1), (0,1,0), (0,1,1)]),
+        ("L", "L", &[(0,0,0), (0,1,0), (0,2,0), (0,2,1)]),
+        ("T", "T", &[(0,0,0), (0,0,1), (0,0,2), (0,1,1)]),
+        ("Z", "Z", &[(0,0,1), (0,0,2), (0,1,0), (0,1,1)]),
+        ("A", "B", &[(0,0,0), (0,0,1), (0,1,1), (1,0,0)]),
+        ("P", "P", &[(0,0,0), (0,0,1), (0,1,1), (1,0,1)]),
+    ];
+
+    // The name of the piece made of `cells` in `table`, the one of its mirror
+    // image if it is one-sided or fixed and reflected from the drawing in the table.
+    fn lookup<C: Cell>(table: &[Named<C>], cells: &[C], orientation: Orientation) -> Option<&'static str> {
+        let mut piece = cells.to_vec();
+        C::normalize(&mut piece);
+        for &(name, mirror_name, shape) in table {
+            if shape.len() != piece.len() {
+                continue;
+            }
+            if C::orientations(shape, Orientation::OneSided).contains(&piece) {
+                return Some(name);
+            }
+            if C::orientations(shape, Orientation::Free).contains(&piece) {
+                return Some(if orientation == Orientation::Free { name } else { mirror_name });
+            }
+        }
+        None
+    }
+
+    /// Splits a piece name such as "L4" into its letters and the size it ends
+    /// with, which tells apart pieces of different sizes with the same letter.
+    pub fn split_size(name: &str) -> (&str, Option<usize>) {
+        let letters = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        (letters, name[letters.len()..].parse().ok())
+    }
+
+    // The cells of the piece called `name` in `table`, of size `size` if given.
+    fn cells_of<C: Cell>(table: &[Named<C>], name: &str, size: Option<usize>) -> Option<Vec<C>> {
+        for &(piece_name, mirror_name, shape) in table {
+            if size.is_some_and(|size| size != shape.len()) {
+                continue;
+            }
+            if piece_name == name {
+                let mut cells = shape.to_vec();
+                C::normalize(&mut cells);
+                return Some(cells);
+            }
+            if mirror_name == name {
+                let rotations = C::orientations(shape, Orientation::OneSided);
+                return C::orientations(shape, Orientation::Free).into_iter().find(|cells| !rotations.contains(cells));
+            }
+        }
+        None
+    }
+
+    /// The standard name of a tetromino or pentomino: I, O, T, L or S for the
+    /// tetrominoes and Conway's F, I, L, N, P, T, U, V, W, X, Y or Z for the
+    /// pentominoes. Unless `orientation` is Free, the mirror images of the
+    /// chiral ones are named J and Z, and F', L', N', P', Y' and Z'. Fixed pieces
+    /// are named like one-sided ones, so rotations of a piece share its name.
+    /// None for pieces of other sizes.
+    pub fn polyomino_name(cells: &[(usize,usize)], orientation: Orientation) -> Option<&'static str> {
+        match cells.len() {
+            4 => lookup(&TETROMINOES, cells, orientation),
+            5 => lookup(&PENTOMINOES, cells, orientation),
+            _ => None,
+        }
+    }
+
+    /// The standard name of the V tricube or a tetracube: V, L, T, Z, A, B and P
+    /// for the pieces of the Soma cube, I and O for the others. The screws A and
+    /// B are mirror images of each other, and both are named A if `orientation`
+    /// is Free. None for other pieces.
+    pub fn polycube_name(cells: &[(usize,usize,usize)], orientation: Orientation) -> Option<&'static str> {
+        lookup(&POLYCUBES, cells, orientation)
+    }
+
+    /// The cells of the tetromino or pentomino called `name`, as polyomino_name
+    /// names them. The letters that both sizes use mean the pentomino unless
+    /// the name ends with the size: "L" and "L5" are the L pentomino, "L4" the
+    /// L tetromino.
+    pub fn polyomino_cells(name: &str) -> Option<Vec<(usize,usize)>> {
+        let (name, size) = split_size(name);
+        cells_of(&PENTOMINOES, name, size).or_else(|| cells_of(&TETROMINOES, name, size))
+    }
+
+    /// The cells of the polycube called `name`, as polycube_name names them,
+    /// possibly followed by its size.
+    pub fn polycube_cells(name: &str) -> Option<Vec<(usize,usize,usize)>> {
+        let (name, size) = split_size(name);
+        cells_of(&POLYCUBES, name, size)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::pieceset::pieceset::{PieceSet, PieceSet3d};
+        use crate::solver::solver::solve_polycube_dlx;
+
+        #[test]
+        fn pentomino_names() {
+            let names = PieceSet::all_polyominoes(&[5], Orientation::Free).unwrap().names();
+            let mut sorted: Vec<&str> = names.iter().map(|name| name.unwrap()).collect();
+            sorted.sort();
+            assert_eq!(sorted, ["F", "I", "L", "N", "P", "T", "U", "V", "W", "X", "Y", "Z"]);
+            for name in sorted {
+                let cells = polyomino_cells(name).unwrap();
+                assert_eq!(polyomino_name(&cells, Orientation::Free), Some(name));
+                assert_eq!(polyomino_name(&cells, Orientation::OneSided), Some(name));
+            }
+
+            // The one-sided pentominoes add the mirror images of the six chiral ones.
+            let names = PieceSet::all_polyominoes(&[5], Orientation::OneSided).unwrap().names();
+            assert_eq!(names.len(), 18);
+            for name in ["F'", "L'", "N'", "P'", "Y'", "Z'"] {
+                assert!(names.contains(&Some(name)), "{}", name);
+                let cells = polyomino_cells(name).unwrap();
+                assert_eq!(polyomino_name(&cells, Orientation::OneSided), Some(name));
+                assert_eq!(polyomino_name(&cells, Orientation::Free), Some(&name[..1]));
+            }
+        }
+
+        #[test]
+        fn names_with_sizes() {
+            assert_eq!(split_size("L4"), ("L", Some(4)));
+            assert_eq!(split_size("F'"), ("F'", None));
+            assert_eq!(polyomino_cells("L"), polyomino_cells("L5"));
+            assert_eq!(polyomino_cells("L").unwrap().len(), 5);
+            assert_eq!(polyomino_cells("L4").unwrap().len(), 4);
+            assert_eq!(polyomino_cells("J").unwrap().len(), 4);
+            assert_eq!(polyomino_cells("L6"), None);
+            assert_eq!(polyomino_cells("Q"), None);
+
+            let pieces = PieceSet::all_polyominoes(&[4, 5], Orientation::Free).unwrap();
+            assert_eq!(pieces.piece(pieces.index_of("L").unwrap()).len(), 5);
+            assert_eq!(pieces.piece(pieces.index_of("L4").unwrap()).len(), 4);
+            assert_eq!(pieces.index_of("O5"), None);
+        }
+
+        #[test]
+        fn soma_cube_by_name() {
+            let soma = PieceSet3d::named(&["V", "L", "T", "Z", "A", "B", "P"]).unwrap();
+            let names: Vec<&str> = soma.names().iter().map(|name| name.unwrap()).collect();
+            assert_eq!(names, ["V", "L", "T", "Z", "A", "B", "P"]);
+            assert_eq!(polycube_name(&polycube_cells("B").unwrap(), Orientation::Free), Some("A"));
+            let cube = vec![vec![vec![0; 3]; 3]; 3];
+            assert_eq!(solve_polycube_dlx(&cube, soma).unwrap().len(), 240);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+pub mod palette {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+    use crate::board::board::Board;
+    use crate::cube::cube::Cube;
+
+    /// Nine well-separated colors (Tableau 10 without its gray, HOLE_COLOR),
+    /// enough for the pieces of any solution to be told apart from
+    /// their neighbors.
+    pub const PALETTE: [(u8,u8,u8); 9] = [
+        (78, 121, 167),  // Blue
+        (242, 142, 43),  // Orange
+        (225, 87, 89),   // Red
+        (118, 183, 178), // Teal
+        (89, 161, 79),   // Green
+        (237, 201, 72),  // Yellow
+        (176, 122, 161), // Purple
+        (255, 157, 167), // Pink
+        (156, 117, 95),  // Brown
+    ];
+
+    /// The gray of Tableau 10, for the holes of a board or cube.
+    pub const HOLE_COLOR: (u8,u8,u8) = (186, 176, 172);
+
+    // Which values share an edge (or a face) with which others, the values being
+    // the vertices of the graph to color.
+    type Graph = BTreeMap<usize, BTreeSet<usize>>;
+
+    fn connect(graph: &mut Graph, a: usize, b: usize) {
+        if a == b {
+            graph.entry(a).or_default();
+            return;
+        }
+        graph.entry(a).or_default().insert(b);
+        graph.entry(b).or_default().insert(a);
+    }
+
+    // A coloring of the graph by DSatur: the vertex with the most distinct colors
+    // around it goes next, and takes the smallest color none of its neighbors
+    // has. Colors are numbered from 0.
+    fn dsatur(graph: &Graph) -> HashMap<usize, usize> {
+        let mut colors: HashMap<usize, usize> = HashMap::new();
+        while colors.len() < graph.len() {
+            let saturation = |v: usize| {
+                graph[&v].iter().filter_map(|u| colors.get(u)).collect::<BTreeSet<_>>().len()
+            };
+            let Some(v) = graph.keys().copied()
+                .filter(|v| !colors.contains_key(v))
+                .max_by_key(|&v| (saturation(v), graph[&v].len(), std::cmp::Reverse(v))) else {
+                break;
+            };
+            let taken: BTreeSet<usize> = graph[&v].iter().filter_map(|u| colors.get(u)).copied().collect();
+            let color = (0..).find(|c| !taken.contains(c)).unwrap_or(0);
+            colors.insert(v, color);
+        }
+        colors
+    }
+
+    // Color number `k` beyond the end of a palette: hues a golden angle apart
+    // around the color wheel, which never repeat and stay well apart from the
+    // last few, at the saturation and brightness of the Tableau colors.
+    fn extra_color(k: usize) -> (u8,u8,u8) {
+        let hue = (k as f64 * 137.508).rem_euclid(360.0) / 60.0;
+        let (saturation, value) = (0.55, if k.is_multiple_of(2) { 0.85 } else { 0.7 });
+        let chroma = saturation * value;
+        let x = chroma * (1.0 - (hue.rem_euclid(2.0) - 1.0).abs());
+        let (r,g,b) = match hue as usize {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f64| ((c + value - chroma) * 255.0).round() as u8;
+        (channel(r), channel(g), channel(b))
+    }
+
+    fn assign(graph: &Graph, palette: &[(u8,u8,u8)]) -> HashMap<usize, (u8,u8,u8)> {
+        dsatur(graph).into_iter()
+            .map(|(value,color)| {
+                (value, palette.get(color).copied().unwrap_or_else(|| extra_color(color - palette.len())))
+            })
+            .collect()
+    }
+
+    /// A color from `palette` for every value of `board` for which `colored`
+    /// holds, such that the pieces (the groups of cells with the same value)
+    /// that share an edge have different colors. Four colors are enough for a
+    /// board, and the greedy coloring seldom needs more than five, so PALETTE is
+    /// plenty; should a palette run out, further colors are made up so that
+    /// neighbors still differ.
+    pub fn board_colors(board: &Board, palette: &[(u8,u8,u8)],
+                        colored: impl Fn(usize) -> bool) -> HashMap<usize, (u8,u8,u8)> {
+        let mut graph = Graph::new();
+        for (i,row) in board.0.iter().enumerate() {
+            for (j,&value) in row.iter().enumerate() {
+                if !colored(value) {
+                    continue;
+                }
+                connect(&mut graph, value, value);
+                for (a,b) in [(i+1,j), (i,j+1)] {
+                    if let Some(&other) = board.0.get(a).and_then(|row| row.get(b)) {
+                        if colored(other) {
+                            connect(&mut graph, value, other);
+                        }
+                    }
+                }
+            }
+        }
+        assign(&graph, palette)
+    }
+
+    /// Same as board_colors for the pieces of `cube`, which touch when they
+    /// share a face. No number of colors is enough for every cube, as a piece
+    /// can touch any number of others, but the 240 solutions of the Soma cube
+    /// take at most five.
+    pub fn cube_colors(cube: &Cube, palette: &[(u8,u8,u8)],
+                       colored: impl Fn(usize) -> bool) -> HashMap<usize, (u8,u8,u8)> {
+        let mut graph = Graph::new();
+        let cell = |i: usize, j: usize, k: usize| {
+            cube.0.get(i).and_then(|plane| plane.get(j)).and_then(|row| row.get(k))
+        };
+        for (i,plane) in cube.0.iter().enumerate() {
+            for (j,row) in plane.iter().enumerate() {
+                for (k,&value) in row.iter().enumerate() {
+                    if !colored(value) {
+                        continue;
+                    }
+                    connect(&mut graph, value, value);
+                    for (a,b,c) in [(i+1,j,k), (i,j+1,k), (i,j,k+1)] {
+                        if let Some(&other) = cell(a, b, c) {
+                            if colored(other) {
+                                connect(&mut graph, value, other);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assign(&graph, palette)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::pieceset::pieceset::PieceSet3d;
+        use crate::solver::solver::solve_polycube_dlx;
+
+        #[test]
+        fn past_the_end_of_the_palette() {
+            // Four pieces that all touch each other, with two colors to go round.
+            let board = Board(vec![vec![1, 1, 1],
+                                   vec![2, 0, 3],
+                                   vec![2, 2, 3]]);
+            let palette = &PALETTE[..2];
+            let colors = board_colors(&board, palette, |_| true);
+            let distinct: BTreeSet<_> = colors.values().copied().collect();
+            assert_eq!(distinct.len(), 4);
+            assert!(palette.iter().all(|color| distinct.contains(color)));
+
+            let extra: BTreeSet<_> = (0..100).map(extra_color).collect();
+            assert_eq!(extra.len(), 100);
+            assert!(!extra.contains(&HOLE_COLOR) && PALETTE.iter().all(|color| !extra.contains(color)));
+        }
+
+        #[test]
+        fn soma_cube_in_five_colors() {
+            let soma = PieceSet3d::named(&["V", "L", "T", "Z", "A", "B", "P"]).unwrap();
+            let solutions = solve_polycube_dlx(&vec![vec![vec![0; 3]; 3]; 3], soma).unwrap();
+            let most = solutions.iter()
+                .map(|cube| cube_colors(cube, &PALETTE, |_| true).values().collect::<BTreeSet<_>>().len())
+                .max();
+            assert_eq!(most, Some(5));
+        }
+    }
+}
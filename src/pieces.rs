@@ -1,386 +1,91 @@
 pub mod piece {
-    use crate::board::board::*;
     use crate::polyominolist::polyominolist::*;
-    
-    pub fn get_num_pieces(n: usize) -> usize {
-        match n {
-            4 => NUM_PIECES_4,
-            5 => NUM_PIECES_5,
-            6 => NUM_PIECES_6,
-            7 => NUM_PIECES_7,
-            8 => NUM_PIECES_8,
-            9 => NUM_PIECES_9,
-            10 => NUM_PIECES_10,
-            _ => panic!("wrong size"),
-        }
-    }
+    use crate::polycubelist::polycubelist::polycubes;
+    use crate::pieceset::pieceset::{PieceSet, Orientation};
+    use crate::error::error::SolverError;
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
 
-    pub fn get_num_pieces_3d(n: usize) -> usize {
-        match n {
-            4 => 8,
-            5 => 29,
-            6 => 166,
-            7 => 1023,
-            8 => 6922,
-            9 => 48311,
-            10 =>346543,
-            _ => panic!("wrong size"),
+    /// Fails for the sizes that have no pieces, which is only 0. Any other size
+    /// can be enumerated, if slowly beyond 10 or so.
+    pub fn check_piece_size(n: usize) -> Result<(), SolverError> {
+        if n == 0 {
+            return Err(SolverError::UnsupportedSize(n));
         }
-    }
-    
-    #[allow(dead_code)]
-    fn pieces2vec<const N: usize, const K: usize>(pieces: [[[usize;N];N];K]) -> Vec<Vec<Vec<usize>>> {
-        pieces.iter()
-            .map(|mat| {
-                mat.iter()
-                    .map(|row| row.to_vec())
-                    .collect::<Vec<Vec<usize>>>()
-            })
-            .collect::<Vec<Vec<Vec<usize>>>>()
+        Ok(())
     }
 
-    pub fn congruent_figures_for_each_piece(n: usize) -> Vec<Vec<Vec<(usize,usize)>>> {
-        let all_pieces = free_polyominos(n);
-        
-        let mut ret = Vec::new();
-        for piece in all_pieces {
-            let mut congruent_figures = Vec::new();
-            let figure = Board(piece);
-            for vertically in [true,false] {
-                for horizontally in [true,false] {
-                    for diagonally in [true,false] {
-                        let normalized_figure = figure
-                            .transform(vertically,horizontally,diagonally)
-                            .normalize_coordinates();
-                        if !congruent_figures.contains(&normalized_figure) {
-                            congruent_figures.push(normalized_figure);
-                        }
-                    }
-                }
-            }
-            ret.push(congruent_figures);
+    // Counts already enumerated, keyed by size, orientation and dimension.
+    type Counts = HashMap<(usize,Orientation,bool), usize>;
+
+    fn cached_count(n: usize, orientation: Orientation, three_d: bool,
+                    count: impl FnOnce() -> usize) -> Result<usize, SolverError> {
+        static COUNTS: OnceLock<Mutex<Counts>> = OnceLock::new();
+        check_piece_size(n)?;
+        let counts = COUNTS.get_or_init(|| Mutex::new(HashMap::new()));
+        let key = (n, orientation, three_d);
+        if let Some(&num) = counts.lock().unwrap().get(&key) {
+            return Ok(num);
         }
-        ret
+        // Not under the lock, as large sizes take a while.
+        let num = count();
+        counts.lock().unwrap().insert(key, num);
+        Ok(num)
     }
 
-    #[allow(dead_code)]
-    const NUM_PIECES_4: usize = 5;
-
-    #[allow(dead_code)]
-    const PIECES_4: [[[usize;4];4]; NUM_PIECES_4] = [
-        [[1,0,0,0],
-         [1,0,0,0],
-         [1,0,0,0],
-         [1,0,0,0]], // 0
-        [[1,1,1,0],
-         [0,1,0,0],
-         [0,0,0,0],
-         [0,0,0,0]], // 1
-        [[1,1,1,0],
-         [1,0,0,0],
-         [0,0,0,0],
-         [0,0,0,0]], // 2
-        [[0,1,1,0],
-         [1,1,0,0],
-         [0,0,0,0],
-         [0,0,0,0]], // 3
-        [[1,1,0,0],
-         [1,1,0,0],
-         [0,0,0,0],
-         [0,0,0,0]], // 4
-    ];
+    /// Number of polyominoes of size n that `orientation` tells apart, as
+    /// polyominos(n, orientation) lists them: 12 free, 18 one-sided or 63 fixed
+    /// pentominoes. The first call for a size enumerates them.
+    pub fn num_polyominoes(n: usize, orientation: Orientation) -> Result<usize, SolverError> {
+        cached_count(n, orientation, false, || polyominos(n, orientation).len())
+    }
 
-    #[allow(dead_code)]
-    const NUM_PIECES_5: usize = 12;
+    /// Same as num_polyominoes for polycubes: 7 free, 8 one-sided or 86 fixed
+    /// tetracubes.
+    pub fn num_polycubes(n: usize, orientation: Orientation) -> Result<usize, SolverError> {
+        cached_count(n, orientation, true, || polycubes(n, orientation).len())
+    }
 
-    #[allow(dead_code)]
-    const PIECES_5: [[[usize;5];5]; NUM_PIECES_5] = [
-        [[1,0,0,0,0],
-         [1,0,0,0,0],
-         [1,0,0,0,0],
-         [1,0,0,0,0],
-         [1,0,0,0,0]], // 0
-        [[0,1,1,0,0],
-         [1,1,0,0,0],
-         [0,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 1
-        [[0,1,0,0,0],
-         [0,1,0,0,0],
-         [0,1,0,0,0],
-         [1,1,0,0,0],
-         [0,0,0,0,0]], // 2
-        [[1,1,0,0,0],
-         [1,1,0,0,0],
-         [0,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 3
-        [[0,1,0,0,0],
-         [0,1,0,0,0],
-         [1,1,0,0,0],
-         [1,0,0,0,0],
-         [0,0,0,0,0]], // 4
-        [[1,1,1,0,0],
-         [0,1,0,0,0],
-         [0,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 5
-        [[1,0,1,0,0],
-         [1,1,1,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 6
-        [[0,0,1,0,0],
-         [0,0,1,0,0],
-         [1,1,1,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 7
-        [[0,0,1,0,0],
-         [0,1,1,0,0],
-         [1,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 8
-        [[0,1,0,0,0],
-         [1,1,1,0,0],
-         [0,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 9
-        [[0,1,0,0,0],
-         [1,1,0,0,0],
-         [0,1,0,0,0],
-         [0,1,0,0,0],
-         [0,0,0,0,0]], // 10
-        [[0,1,1,0,0],
-         [0,1,0,0,0],
-         [1,1,0,0,0],
-         [0,0,0,0,0],
-         [0,0,0,0,0]], // 11
-    ];
+    /// Number of free polyominoes of size n, the pieces that a size stands for.
+    pub fn get_num_pieces(n: usize) -> Result<usize, SolverError> {
+        num_polyominoes(n, Orientation::Free)
+    }
 
-    #[allow(dead_code)]
-    const NUM_PIECES_6: usize = 35;
+    /// Number of one-sided polycubes of size n, the pieces that a size stands for.
+    pub fn get_num_pieces_3d(n: usize) -> Result<usize, SolverError> {
+        num_polycubes(n, Orientation::OneSided)
+    }
+    
+    /// The orientations of every polyomino of size n that `orientation` tells
+    /// apart, see PieceSet::orientations.
+    pub fn congruent_figures_for_each_piece(n: usize, orientation: Orientation) -> Vec<Vec<Vec<(usize,usize)>>> {
+        PieceSet::from_boards(&polyominos(n, orientation))
+            .map_or(Vec::new(), |pieces| pieces.with_orientation(orientation).orientations())
+    }
 
-    #[allow(dead_code)]
-    const PIECES_6: [[[usize;6];6]; NUM_PIECES_6] = [
-        [[1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0]], // 0
-        [[1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 1
-        [[1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 2
-        [[1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 3
-        [[0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 4
-        [[1,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 5
-        [[1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 6
-        [[1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 7
-        [[1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 8
-        [[1,1,1,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 9
-        [[1,0,0,0,0,0],
-         [1,1,1,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 10
-        [[1,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 11
-        [[0,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 12
-        [[0,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 13
-        [[0,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 14
-        [[0,1,0,0,0,0],
-         [0,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 15
-        [[0,1,0,0,0,0],
-         [1,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 16
-        [[0,1,0,0,0,0],
-         [1,1,1,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 17
-        [[0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 18
-        [[0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 19
-        [[0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 20
-        [[1,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 21
-        [[0,0,1,0,0,0],
-         [1,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 22
-        [[1,1,1,0,0,0],
-         [0,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 23
-        [[0,0,1,0,0,0],
-         [0,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 24
-        [[0,0,1,0,0,0],
-         [1,1,1,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 25
-        [[0,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 26
-        [[1,1,1,0,0,0],
-         [1,0,1,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 27
-        [[1,0,1,0,0,0],
-         [1,1,1,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 28
-        [[1,0,1,0,0,0],
-         [1,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 29
-        [[0,1,1,0,0,0],
-         [0,1,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 30
-        [[1,0,0,0,0,0],
-         [1,1,0,0,0,0],
-         [1,1,1,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 31
-        [[0,1,0,0,0,0],
-         [1,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 32
-        [[0,0,1,0,0,0],
-         [1,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 33
-        [[0,0,1,0,0,0],
-         [0,1,1,0,0,0],
-         [1,1,0,0,0,0],
-         [1,0,0,0,0,0],
-         [0,0,0,0,0,0],
-         [0,0,0,0,0,0]], // 34
-    ];
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-    #[allow(dead_code)]
-    const NUM_PIECES_7: usize = 108;
-    #[allow(dead_code)]
-    const NUM_PIECES_8: usize = 369;
-    #[allow(dead_code)]
-    const NUM_PIECES_9: usize = 1285;
-    #[allow(dead_code)]
-    const NUM_PIECES_10: usize = 4655;
+        #[test]
+        fn counts_match_the_enumerators() {
+            for orientation in [Orientation::Free, Orientation::OneSided, Orientation::Fixed] {
+                for n in 1..=6 {
+                    let count = polyominos(n, orientation).len();
+                    assert_eq!(num_polyominoes(n, orientation).unwrap(), count);
+                    // Again, from the cache.
+                    assert_eq!(num_polyominoes(n, orientation).unwrap(), count);
+                    assert_eq!(congruent_figures_for_each_piece(n, orientation).len(), count);
+                }
+                for n in 1..=4 {
+                    let count = polycubes(n, orientation).len();
+                    assert_eq!(num_polycubes(n, orientation).unwrap(), count);
+                    assert_eq!(num_polycubes(n, orientation).unwrap(), count);
+                }
+            }
+            assert_eq!((get_num_pieces(5).unwrap(), get_num_pieces_3d(4).unwrap()), (12, 8));
+            assert_eq!(num_polyominoes(0, Orientation::Free), Err(SolverError::UnsupportedSize(0)));
+            assert_eq!(num_polycubes(0, Orientation::Free), Err(SolverError::UnsupportedSize(0)));
+        }
+    }
 }
-
@@ -0,0 +1,405 @@
+pub mod pieceset {
+    use crate::board::board::Board;
+    use crate::cube::cube::Cube;
+    use crate::solutionset::solutionset::Transformable;
+    use crate::polyominolist::polyominolist::polyominos;
+    use crate::polycubelist::polycubelist::polycubes;
+    use crate::pieces::piece::check_piece_size;
+    use crate::error::error::SolverError;
+    use crate::names::names::{polyomino_name, polycube_name, polyomino_cells, polycube_cells, split_size};
+    use std::ops::RangeInclusive;
+
+    /// How the pieces may be turned when they are placed.
+    #[derive(Clone,Copy,Debug,Default,PartialEq,Eq,Hash)]
+    pub enum Orientation {
+        /// Rotated and flipped over, so that a piece and its mirror image are
+        /// the same piece. For polycubes this takes a mirror, which no solid
+        /// piece can do.
+        #[default]
+        Free,
+        /// Rotated but not flipped over, so that a piece and its mirror image
+        /// are different pieces.
+        OneSided,
+        /// Placed the way they are given, only moved.
+        Fixed,
+    }
+
+    /// The cell of a piece: (row, column) for polyominoes, (layer, row, column)
+    /// for polycubes.
+    pub trait Cell: Copy + Ord + std::hash::Hash + std::fmt::Debug {
+        /// How pieces of this kind are turned unless told otherwise: Free for
+        /// polyominoes, OneSided for polycubes, as real pieces can be.
+        const DEFAULT_ORIENTATION: Orientation;
+
+        /// Shifts `cells` so that every coordinate starts at 0, and sorts them.
+        fn normalize(cells: &mut Vec<Self>);
+
+        /// The distinct normalized orientations of the piece made of `cells`
+        /// that `orientation` allows, starting with `cells` itself.
+        fn orientations(cells: &[Self], orientation: Orientation) -> Vec<Vec<Self>>;
+
+        /// The standard name of the piece made of `cells`, if it has one, see
+        /// names::polyomino_name and names::polycube_name.
+        fn name(cells: &[Self], orientation: Orientation) -> Option<&'static str>;
+
+        /// The cells of the piece called `name`, see names::polyomino_cells and
+        /// names::polycube_cells.
+        fn named(name: &str) -> Option<Vec<Self>>;
+    }
+
+    // The distinct figures among the transformations of `grid` that `orientation`
+    // allows, each one normalized by `normalize`.
+    fn distinct_figures<T: Transformable, C: Cell>(grid: T,
+                                                   orientation: Orientation,
+                                                   normalize: impl Fn(&T) -> Vec<C>) -> Vec<Vec<C>> {
+        let transformations = match orientation {
+            Orientation::Free => grid.get_all_transformations(),
+            Orientation::OneSided => grid.get_rotations(),
+            Orientation::Fixed => vec![grid],
+        };
+        let mut ret: Vec<Vec<C>> = Vec::new();
+        for figure in transformations {
+            let figure = normalize(&figure);
+            if !ret.contains(&figure) {
+                ret.push(figure);
+            }
+        }
+        ret
+    }
+
+    impl Cell for (usize,usize) {
+        const DEFAULT_ORIENTATION: Orientation = Orientation::Free;
+
+        fn normalize(cells: &mut Vec<Self>) {
+            let min_i = cells.iter().map(|c| c.0).min().unwrap_or(0);
+            let min_j = cells.iter().map(|c| c.1).min().unwrap_or(0);
+            cells.iter_mut().for_each(|(i,j)| {
+                *i -= min_i;
+                *j -= min_j;
+            });
+            cells.sort();
+            cells.dedup();
+        }
+
+        fn orientations(cells: &[Self], orientation: Orientation) -> Vec<Vec<Self>> {
+            // A square grid, so that it can be flipped along the diagonal.
+            let size = cells.iter().map(|&(i,j)| i.max(j) + 1).max().unwrap_or(0);
+            let mut grid = Board::new(size, size);
+            for &(i,j) in cells {
+                *grid.get_mut(i,j) = 1;
+            }
+            distinct_figures(grid, orientation, Board::normalize_coordinates)
+        }
+
+        fn name(cells: &[Self], orientation: Orientation) -> Option<&'static str> {
+            polyomino_name(cells, orientation)
+        }
+
+        fn named(name: &str) -> Option<Vec<Self>> {
+            polyomino_cells(name)
+        }
+    }
+
+    impl Cell for (usize,usize,usize) {
+        const DEFAULT_ORIENTATION: Orientation = Orientation::OneSided;
+
+        fn normalize(cells: &mut Vec<Self>) {
+            let min_i = cells.iter().map(|c| c.0).min().unwrap_or(0);
+            let min_j = cells.iter().map(|c| c.1).min().unwrap_or(0);
+            let min_k = cells.iter().map(|c| c.2).min().unwrap_or(0);
+            cells.iter_mut().for_each(|(i,j,k)| {
+                *i -= min_i;
+                *j -= min_j;
+                *k -= min_k;
+            });
+            cells.sort();
+            cells.dedup();
+        }
+
+        fn orientations(cells: &[Self], orientation: Orientation) -> Vec<Vec<Self>> {
+            // A cubical grid, so that all 24 rotations and their mirrors apply.
+            let size = cells.iter().map(|&(i,j,k)| i.max(j).max(k) + 1).max().unwrap_or(0);
+            let mut grid = Cube::new(size, size, size);
+            for &(i,j,k) in cells {
+                *grid.get_mut(i,j,k) = 1;
+            }
+            distinct_figures(grid, orientation, Cube::normalize_coordinates)
+        }
+
+        fn name(cells: &[Self], orientation: Orientation) -> Option<&'static str> {
+            polycube_name(cells, orientation)
+        }
+
+        fn named(name: &str) -> Option<Vec<Self>> {
+            polycube_cells(name)
+        }
+    }
+
+    /// The pieces of a puzzle, each one in a single orientation. The solvers
+    /// place them in every orientation that the Orientation of the piece allows,
+    /// Cell::DEFAULT_ORIENTATION unless with_orientation says otherwise. Pieces keep the order they
+    /// were given in, and solutions label them by their index in the set.
+    #[derive(Clone,Debug,PartialEq,Eq,Hash)]
+    pub struct PieceSet<C: Cell = (usize,usize)> {
+        pieces: Vec<Vec<C>>,
+        modes: Vec<Orientation>,
+    }
+
+    /// A set of polycubes.
+    pub type PieceSet3d = PieceSet<(usize,usize,usize)>;
+
+    impl<C: Cell> PieceSet<C> {
+        /// A set of the pieces made of the given cells. Fails if a piece has no cells.
+        pub fn new(pieces: Vec<Vec<C>>) -> Result<Self, SolverError> {
+            let mut pieces = pieces;
+            for (k,piece) in pieces.iter_mut().enumerate() {
+                if piece.is_empty() {
+                    return Err(SolverError::EmptyPiece(k));
+                }
+                C::normalize(piece);
+            }
+            let modes = vec![C::DEFAULT_ORIENTATION; pieces.len()];
+            Ok(PieceSet { pieces, modes })
+        }
+
+        /// The same pieces, all of them turned as `orientation` allows.
+        pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+            self.modes.fill(orientation);
+            self
+        }
+
+        /// How piece k may be turned.
+        pub fn orientation(&self, k: usize) -> Orientation {
+            self.modes[k]
+        }
+
+        pub fn len(&self) -> usize {
+            self.pieces.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.pieces.is_empty()
+        }
+
+        /// The cells of piece k.
+        pub fn piece(&self, k: usize) -> &[C] {
+            &self.pieces[k]
+        }
+
+        pub fn pieces(&self) -> &[Vec<C>] {
+            &self.pieces
+        }
+
+        /// Number of cells of every piece.
+        pub fn sizes(&self) -> Vec<usize> {
+            self.pieces.iter().map(|piece| piece.len()).collect()
+        }
+
+        /// Number of cells of the largest piece.
+        pub fn max_size(&self) -> usize {
+            self.pieces.iter().map(|piece| piece.len()).max().unwrap_or(0)
+        }
+
+        /// Number of cells covered by `copies[k]` copies of every piece k.
+        pub fn area(&self, copies: &[usize]) -> usize {
+            self.pieces.iter().zip(copies).map(|(piece,&count)| piece.len() * count).sum()
+        }
+
+        /// The greatest common divisor of the sizes of the pieces that have
+        /// copies, which divides the size of every region they can fill. It is
+        /// the piece size if all of them have the same size, and 0 if none is used.
+        pub fn region_unit(&self, copies: &[usize]) -> usize {
+            fn gcd(a: usize, b: usize) -> usize {
+                if b == 0 { a } else { gcd(b, a % b) }
+            }
+            self.pieces.iter().zip(copies)
+                .filter(|&(_,&count)| count > 0)
+                .fold(0, |unit,(piece,_)| gcd(unit, piece.len()))
+        }
+
+        /// The pieces at `indices`, in that order.
+        pub fn subset(&self, indices: &[usize]) -> Result<Self, SolverError> {
+            let (pieces, modes) = indices.iter()
+                .map(|&index| {
+                    self.pieces.get(index)
+                        .map(|piece| (piece.clone(), self.modes[index]))
+                        .ok_or(SolverError::UnknownPiece { index, num_pieces: self.len() })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .unzip();
+            Ok(PieceSet { pieces, modes })
+        }
+
+        /// The pieces of self followed by those of `other`, which may have other
+        /// sizes and orientations.
+        pub fn concat(&self, other: &Self) -> Self {
+            PieceSet { pieces: self.pieces.iter().chain(&other.pieces).cloned().collect(),
+                       modes: self.modes.iter().chain(&other.modes).copied().collect() }
+        }
+
+        /// The pieces called `names`, in that order, such as `&["V", "L", "T", "Z",
+        /// "A", "B", "P"]` for the Soma cube. A name may end with the size of the
+        /// piece to tell apart pieces of different sizes with the same letter.
+        pub fn named(names: &[&str]) -> Result<Self, SolverError> {
+            let pieces = names.iter()
+                .map(|&name| C::named(name).ok_or_else(|| SolverError::UnknownPieceName(name.to_string())))
+                .collect::<Result<Vec<_>, _>>()?;
+            PieceSet::new(pieces)
+        }
+
+        /// The standard name of piece k, as it is placed: the mirror image of a
+        /// one-sided piece has a name of its own. None if it has no name.
+        pub fn name(&self, k: usize) -> Option<&'static str> {
+            C::name(&self.pieces[k], self.modes[k])
+        }
+
+        /// The names of all the pieces, which solutions number in this order.
+        pub fn names(&self) -> Vec<Option<&'static str>> {
+            (0..self.len()).map(|k| self.name(k)).collect()
+        }
+
+        /// The index of the first piece called `name`, of the size that named
+        /// gives it: "L" is an L pentomino, "L4" an L tetromino.
+        pub fn index_of(&self, name: &str) -> Option<usize> {
+            let (letters, _) = split_size(name);
+            let size = C::named(name)?.len();
+            (0..self.len()).find(|&k| self.pieces[k].len() == size && self.name(k) == Some(letters))
+        }
+
+        /// The pieces of self called `names`, in that order, as subset picks them
+        /// by index.
+        pub fn select(&self, names: &[&str]) -> Result<Self, SolverError> {
+            let indices = names.iter()
+                .map(|&name| self.index_of(name).ok_or_else(|| SolverError::UnknownPieceName(name.to_string())))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.subset(&indices)
+        }
+
+        /// The distinct orientations of every piece.
+        pub fn orientations(&self) -> Vec<Vec<Vec<C>>> {
+            self.pieces.iter().zip(&self.modes).map(|(piece,&mode)| C::orientations(piece, mode)).collect()
+        }
+    }
+
+    impl PieceSet {
+        /// All the free polyominoes of size n, in the order of free_polyominos(n).
+        pub fn polyominoes(n: usize) -> Result<Self, SolverError> {
+            PieceSet::all_polyominoes(&[n], Orientation::Free)
+        }
+
+        /// All the free polyominoes of each of the sizes in `orders`, one size
+        /// after the other.
+        pub fn polyominoes_of_orders(orders: &[usize]) -> Result<Self, SolverError> {
+            PieceSet::all_polyominoes(orders, Orientation::Free)
+        }
+
+        /// All the polyominoes of each of the sizes in `orders` that are distinct
+        /// under `orientation`, in the order of polyominos(n, orientation), to be
+        /// placed that way: the 18 one-sided pentominoes for `&[5]` and OneSided,
+        /// say.
+        pub fn all_polyominoes(orders: &[usize], orientation: Orientation) -> Result<Self, SolverError> {
+            let mut boards = Vec::new();
+            for &n in orders {
+                check_piece_size(n)?;
+                boards.extend(polyominos(n, orientation));
+            }
+            Ok(PieceSet::from_boards(&boards)?.with_orientation(orientation))
+        }
+
+        /// One piece per board, made of its non-zero cells.
+        pub fn from_boards(boards: &[Vec<Vec<usize>>]) -> Result<Self, SolverError> {
+            PieceSet::new(boards.iter().map(|board| Board(board.clone()).normalize_coordinates()).collect())
+        }
+    }
+
+    impl PieceSet3d {
+        /// All the one-sided polycubes of size n, in the order of
+        /// polycubes(n, OneSided): a piece and its mirror image are two pieces,
+        /// as with the 8 tetracubes of the Soma cube and its relatives.
+        pub fn polycubes(n: usize) -> Result<Self, SolverError> {
+            PieceSet::all_polycubes(&[n], Orientation::OneSided)
+        }
+
+        /// Same as polyominoes_of_orders for one-sided polycubes.
+        pub fn polycubes_of_orders(orders: &[usize]) -> Result<Self, SolverError> {
+            PieceSet::all_polycubes(orders, Orientation::OneSided)
+        }
+
+        /// Same as all_polyominoes for polycubes: the 7 free tetracubes for `&[4]`
+        /// and Free, the 8 one-sided ones for OneSided.
+        pub fn all_polycubes(orders: &[usize], orientation: Orientation) -> Result<Self, SolverError> {
+            let mut cubes: Vec<Vec<Vec<Vec<usize>>>> = Vec::new();
+            for &n in orders {
+                check_piece_size(n)?;
+                cubes.extend(polycubes(n, orientation).iter().map(|piece| {
+                    piece.iter()
+                        .map(|plane| plane.iter().map(|row| row.iter().map(|&v| v as usize).collect()).collect())
+                        .collect()
+                }));
+            }
+            Ok(PieceSet::from_cubes(&cubes)?.with_orientation(orientation))
+        }
+
+        /// One piece per cube, made of its non-zero cells.
+        pub fn from_cubes(cubes: &[Vec<Vec<Vec<usize>>>]) -> Result<Self, SolverError> {
+            PieceSet::new(cubes.iter().map(|cube| Cube(cube.clone()).normalize_coordinates()).collect())
+        }
+    }
+
+    /// What the solvers accept as the pieces of a puzzle: a size n, which stands
+    /// for all the pieces of that size (free polyominoes, one-sided polycubes), a
+    /// list or range of sizes such as `1..=5`, which stands for all the pieces of
+    /// each of them, or a PieceSet.
+    pub trait IntoPieceSet<C: Cell = (usize,usize)> {
+        fn into_piece_set(self) -> Result<PieceSet<C>, SolverError>;
+    }
+
+    impl IntoPieceSet for usize {
+        fn into_piece_set(self) -> Result<PieceSet, SolverError> {
+            PieceSet::polyominoes(self)
+        }
+    }
+
+    impl IntoPieceSet<(usize,usize,usize)> for usize {
+        fn into_piece_set(self) -> Result<PieceSet3d, SolverError> {
+            PieceSet::polycubes(self)
+        }
+    }
+
+    impl IntoPieceSet for &[usize] {
+        fn into_piece_set(self) -> Result<PieceSet, SolverError> {
+            PieceSet::polyominoes_of_orders(self)
+        }
+    }
+
+    impl IntoPieceSet<(usize,usize,usize)> for &[usize] {
+        fn into_piece_set(self) -> Result<PieceSet3d, SolverError> {
+            PieceSet::polycubes_of_orders(self)
+        }
+    }
+
+    impl IntoPieceSet for RangeInclusive<usize> {
+        fn into_piece_set(self) -> Result<PieceSet, SolverError> {
+            PieceSet::polyominoes_of_orders(&self.collect::<Vec<_>>())
+        }
+    }
+
+    impl IntoPieceSet<(usize,usize,usize)> for RangeInclusive<usize> {
+        fn into_piece_set(self) -> Result<PieceSet3d, SolverError> {
+            PieceSet::polycubes_of_orders(&self.collect::<Vec<_>>())
+        }
+    }
+
+    impl<C: Cell> IntoPieceSet<C> for PieceSet<C> {
+        fn into_piece_set(self) -> Result<PieceSet<C>, SolverError> {
+            Ok(self)
+        }
+    }
+
+    impl<C: Cell> IntoPieceSet<C> for &PieceSet<C> {
+        fn into_piece_set(self) -> Result<PieceSet<C>, SolverError> {
+            Ok(self.clone())
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod polycubelist {
     use std::collections::HashSet;
+    use crate::pieceset::pieceset::Orientation;
 
     macro_rules! set_value_3d {
         ($array:expr, $loc:expr, $value:expr) => {{
@@ -23,6 +24,7 @@ pub mod polycubelist {
         }};
     }
 
+    #[allow(clippy::too_many_arguments, clippy::len_zero)]
     fn dfs(loc: &[usize; 3],
            required_cells: u32,
            mut next_number: u32,
@@ -77,7 +79,9 @@ pub mod polycubelist {
             }
         }
 
-        let border_list: Vec<_> = border.clone().into_iter().collect();
+        // Sorted, so that the pieces always come out in the same order.
+        let mut border_list: Vec<_> = border.clone().into_iter().collect();
+        border_list.sort();
         for new_loc in border_list {
             if let Some(v) = get_value_3d!(numbered, &new_loc) {
                 if v > max_visited {
@@ -140,6 +144,7 @@ pub mod polycubelist {
         solutions
     }
 
+    #[allow(clippy::ptr_arg, clippy::needless_range_loop)]
     fn normalize(omino: &Vec<Vec<Vec<bool>>>, n: usize) -> Vec<Vec<Vec<bool>>> {
         let dim0 = omino.len();
         let dim1 = omino[0].len();
@@ -176,6 +181,7 @@ pub mod polycubelist {
         omino2
     }
 
+    #[allow(clippy::ptr_arg)]
     fn rotate_k(cube: &Vec<Vec<Vec<bool>>>, k: usize) -> Vec<Vec<Vec<bool>>> {
         let n = cube.len();
         let mut rotated = vec![vec![vec![false; n]; n]; n];
@@ -215,18 +221,53 @@ pub mod polycubelist {
         rotated
     }
 
-    fn generate_congruent_shapes(omino: &Vec<Vec<Vec<bool>>>) -> Vec<Vec<Vec<Vec<bool>>>> {
+    fn mirror(cube: &[Vec<Vec<bool>>]) -> Vec<Vec<Vec<bool>>> {
+        cube.iter()
+            .map(|plane| plane.iter().map(|row| row.iter().rev().copied().collect()).collect())
+            .collect()
+    }
+
+    // The shapes that count as the same polycube as `omino` under `orientation`:
+    // its 24 rotations, and those of its mirror image as well if it is free.
+    fn generate_congruent_shapes(omino: &Vec<Vec<Vec<bool>>>, orientation: Orientation) -> Vec<Vec<Vec<Vec<bool>>>> {
         let mut congruent_shapes = Vec::new();
         let n = omino.len();
-        for k in 0..24 {
-            let o = rotate_k(omino, k);
-            let o_norm = normalize(&o, n);
-            congruent_shapes.push(o_norm);
+        let mut originals = vec![omino.to_vec()];
+        match orientation {
+            Orientation::Free => originals.push(mirror(omino)),
+            Orientation::OneSided => (),
+            Orientation::Fixed => return vec![normalize(omino, n)],
+        }
+        for original in &originals {
+            for k in 0..24 {
+                let o = rotate_k(original, k);
+                let o_norm = normalize(&o, n);
+                congruent_shapes.push(o_norm);
+            }
         }
         congruent_shapes
     }
 
+    /// The free polycubes of size n, a polycube and its mirror image being the
+    /// same: 7 tetracubes and 23 pentacubes.
     pub fn free_polycubes(n: usize) -> Vec<Vec<Vec<Vec<bool>>>> {
+        polycubes(n, Orientation::Free)
+    }
+
+    /// The polycubes of size n, one of each class of polycubes that `orientation`
+    /// tells apart: 7 free, 8 one-sided (rotations only, as for solid pieces) or
+    /// 86 fixed tetracubes. Each one is an n x n x n grid with the piece in its
+    /// first corner.
+    pub fn polycubes(n: usize, orientation: Orientation) -> Vec<Vec<Vec<Vec<bool>>>> {
+        if orientation == Orientation::Fixed {
+            // The candidates only hold one rotation of some polycubes, so the
+            // fixed ones are the distinct rotations of the one-sided ones.
+            let mut seen: HashSet<Vec<Vec<Vec<bool>>>> = HashSet::new();
+            return polycubes(n, Orientation::OneSided).iter()
+                .flat_map(|omino| generate_congruent_shapes(omino, Orientation::OneSided))
+                .filter(|cshape| seen.insert(cshape.clone()))
+                .collect();
+        }
         let candidates = generate_polycube_candidates(n);
         let mut polycube_all: HashSet<Vec<Vec<Vec<bool>>>> = HashSet::new();
         let mut polycube_nodup = Vec::new();
@@ -234,7 +275,7 @@ pub mod polycubelist {
         for cuboid in candidates {
             let normalized_cube = normalize(&cuboid, n);
             if !polycube_all.contains(&normalized_cube) {
-                for cshape in generate_congruent_shapes(&normalized_cube) {
+                for cshape in generate_congruent_shapes(&normalized_cube, orientation) {
                     polycube_all.insert(cshape);
                 }
                 polycube_nodup.push(normalized_cube);
@@ -242,4 +283,22 @@ pub mod polycubelist {
         }
         polycube_nodup
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn counts_by_orientation() {
+            // OEIS A038119, A000162 and A001931.
+            let expected = [(Orientation::Free, [1, 1, 2, 7, 23]),
+                            (Orientation::OneSided, [1, 1, 2, 8, 29]),
+                            (Orientation::Fixed, [1, 3, 15, 86, 534])];
+            for (orientation, counts) in expected {
+                let found: Vec<usize> = (1..=5).map(|n| polycubes(n, orientation).len()).collect();
+                assert_eq!(found, counts, "{:?}", orientation);
+            }
+            assert_eq!(free_polycubes(4), polycubes(4, Orientation::Free));
+        }
+    }
 }
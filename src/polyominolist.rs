@@ -1,6 +1,8 @@
 pub mod polyominolist {
     use std::collections::HashSet;
+    use crate::pieceset::pieceset::Orientation;
 
+    #[allow(clippy::too_many_arguments)]
     fn dfs(i: usize,
            j: usize,
            last: usize,
@@ -40,7 +42,9 @@ pub mod polyominolist {
         }
 
         // Places where you can insert cells
-        let border_list: Vec<(usize, usize)> = border.iter().cloned().collect();
+        // Sorted, so that the pieces always come out in the same order.
+        let mut border_list: Vec<(usize, usize)> = border.iter().cloned().collect();
+        border_list.sort();
         for (ni, nj) in border_list {
             if let Some(order_value) = order[ni][nj] {
                 if order_value > *max_visited {
@@ -112,6 +116,7 @@ pub mod polyominolist {
         ominos
     }
 
+    #[allow(clippy::ptr_arg, clippy::needless_range_loop)]
     fn normalize(omino: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
         let height = omino.len();
         let width = omino[0].len();
@@ -143,12 +148,24 @@ pub mod polyominolist {
         omino2
     }
 
-    fn congruent_forms(omino: &Vec<Vec<usize>>) -> Vec<Vec<Vec<usize>>> {
+    // The forms of `omino` that count as the same polyomino under `orientation`.
+    #[allow(clippy::ptr_arg)]
+    fn congruent_forms(omino: &Vec<Vec<usize>>, orientation: Orientation) -> Vec<Vec<Vec<usize>>> {
         let mut cforms = Vec::new();
 
         for &horizontally in &[true, false] {
             for &vertically in &[true, false] {
                 for &diagonally in &[true, false] {
+                    let flips = [horizontally, vertically, diagonally].iter().filter(|&&flip| flip).count();
+                    let allowed = match orientation {
+                        Orientation::Free => true,
+                        // An odd number of flips is a mirror image.
+                        Orientation::OneSided => flips % 2 == 0,
+                        Orientation::Fixed => flips == 0,
+                    };
+                    if !allowed {
+                        continue;
+                    }
                     let mut o = omino.clone();
                     if diagonally {
                         // Transpose the matrix
@@ -175,6 +192,13 @@ pub mod polyominolist {
     }
 
     pub fn free_polyominos(n: usize) -> Vec<Vec<Vec<usize>>> {
+        polyominos(n, Orientation::Free)
+    }
+
+    /// The polyominoes of size n, one of each class of polyominoes that
+    /// `orientation` tells apart: 12 free, 18 one-sided or 63 fixed pentominoes.
+    /// Each one is an n x n grid with the piece in its top left corner.
+    pub fn polyominos(n: usize, orientation: Orientation) -> Vec<Vec<Vec<usize>>> {
         let ominos = enumerate_n_omino(n);
         let mut ominos_all: HashSet<Vec<Vec<usize>>> = HashSet::new();
         let mut ominos2 = Vec::new();
@@ -182,11 +206,29 @@ pub mod polyominolist {
             let norm_o = normalize(o);
             if !ominos_all.contains(&norm_o) {
                 ominos2.push(norm_o.clone());
-                for norm_o2 in congruent_forms(&norm_o) {
+                for norm_o2 in congruent_forms(&norm_o, orientation) {
                     ominos_all.insert(norm_o2);
                 }
             }
         }
         ominos2
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn counts_by_orientation() {
+            // OEIS A000105, A000988 and A001168.
+            let expected = [(Orientation::Free, [1, 1, 2, 5, 12, 35, 108]),
+                            (Orientation::OneSided, [1, 1, 2, 7, 18, 60, 196]),
+                            (Orientation::Fixed, [1, 2, 6, 19, 63, 216, 760])];
+            for (orientation, counts) in expected {
+                let found: Vec<usize> = (1..=7).map(|n| polyominos(n, orientation).len()).collect();
+                assert_eq!(found, counts, "{:?}", orientation);
+            }
+            assert_eq!(free_polyominos(5), polyominos(5, Orientation::Free));
+        }
+    }
 }
@@ -0,0 +1,196 @@
+pub mod render {
+    use crate::board::board::Board;
+    use crate::cube::cube::Cube;
+    use crate::pieceset::pieceset::{Cell, PieceSet};
+
+    // The box-drawing character for a corner with lines going up, down, left
+    // and right from it.
+    fn corner(up: bool, down: bool, left: bool, right: bool) -> char {
+        const CORNERS: [char; 16] = [' ', '╶', '╴', '─', '╷', '┌', '┐', '┬',
+                                     '╵', '└', '┘', '┴', '│', '├', '┤', '┼'];
+        CORNERS[(up as usize) << 3 | (down as usize) << 2 | (left as usize) << 1 | right as usize]
+    }
+
+    /// The labels of `pieces` by index: the standard name of the piece if it
+    /// has one, else its index. A solution labels each copy of a piece with the
+    /// label of its kind, see SolveOptions::instance_kinds.
+    pub fn piece_labels<C: Cell>(pieces: &PieceSet<C>) -> Vec<String> {
+        pieces.names().iter().enumerate()
+            .map(|(k,name)| name.map_or_else(|| k.to_string(), |name| name.to_string()))
+            .collect()
+    }
+
+    /// Draws `board` in plain text, with Unicode box-drawing lines around every
+    /// piece and the label of the piece in each of its cells. `label` maps the
+    /// value of a cell to its label, or to None for a hole, which is left blank:
+    /// `|v| labels.get(v).cloned()` with a label for each copy of the pieces,
+    /// see piece_labels. Lines run between neighbouring cells that hold
+    /// different values, so they also part two copies of a piece.
+    #[allow(clippy::needless_range_loop)]
+    pub fn render_board(board: &Board, label: impl Fn(usize) -> Option<String>) -> String {
+        let (h,w) = (board.height(), board.width());
+        let labels: Vec<Vec<Option<String>>> = board.0.iter()
+            .map(|row| row.iter().map(|&value| label(value)).collect())
+            .collect();
+        // The piece at (i-1,j-1), None for holes and outside the board.
+        let piece = |i: usize, j: usize| -> Option<usize> {
+            if i == 0 || j == 0 || i > h || j > w {
+                return None;
+            }
+            labels[i-1][j-1].as_ref().map(|_| board.0[i-1][j-1])
+        };
+        let label_width = labels.iter().flatten().flatten().map(|text| text.chars().count()).max().unwrap_or(0);
+        let cell_width = label_width.max(1) + 2;
+
+        let mut lines = Vec::new();
+        for i in 0..=h {
+            // The boundary above row i, and then row i itself.
+            let mut boundary = String::new();
+            for j in 0..=w {
+                let (nw,ne,sw,se) = (piece(i,j), piece(i,j+1), piece(i+1,j), piece(i+1,j+1));
+                boundary.push(corner(nw != ne, sw != se, nw != sw, ne != se));
+                if j < w {
+                    let edge = if ne != se { '─' } else { ' ' };
+                    boundary.extend(std::iter::repeat_n(edge, cell_width));
+                }
+            }
+            lines.push(boundary);
+            if i == h {
+                break;
+            }
+            let mut row = String::new();
+            for j in 0..=w {
+                row.push(if piece(i+1,j) != piece(i+1,j+1) { '│' } else { ' ' });
+                if j < w {
+                    row.push_str(&format!("{:^width$}", labels[i][j].as_deref().unwrap_or(""), width = cell_width));
+                }
+            }
+            lines.push(row);
+        }
+        lines.iter().map(|line| format!("{}\n", line.trim_end())).collect()
+    }
+
+    /// Places the multi-line `blocks` next to each other, `gap` columns apart and
+    /// as many on a line as fit in `width` columns, then the next ones below
+    /// them after a blank line.
+    pub fn side_by_side(blocks: &[String], gap: usize, width: usize) -> String {
+        let block_width = |block: &String| block.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut rows: Vec<Vec<&String>> = Vec::new();
+        let mut used = 0;
+        for block in blocks {
+            match rows.last_mut() {
+                Some(row) if used + gap + block_width(block) <= width => {
+                    used += gap + block_width(block);
+                    row.push(block);
+                }
+                _ => {
+                    used = block_width(block);
+                    rows.push(vec![block]);
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        for row in rows {
+            let height = row.iter().map(|block| block.lines().count()).max().unwrap_or(0);
+            let mut lines = vec![String::new(); height];
+            for (k,block) in row.iter().enumerate() {
+                let pad = if k + 1 < row.len() { block_width(block) + gap } else { 0 };
+                for (i,line) in lines.iter_mut().enumerate() {
+                    let text = block.lines().nth(i).unwrap_or("");
+                    line.push_str(&format!("{:<pad$}", text, pad = pad));
+                }
+            }
+            out.push(lines.iter().map(|line| format!("{}\n", line.trim_end())).collect::<String>());
+        }
+        out.join("\n")
+    }
+
+    /// Draws `cube` layer by layer, each layer as render_board draws a board,
+    /// side by side in `width` columns. A piece that spans several layers shows
+    /// in each of them with the same label.
+    pub fn render_cube(cube: &Cube, label: impl Fn(usize) -> Option<String>, width: usize) -> String {
+        let blocks: Vec<String> = cube.0.iter().enumerate()
+            .map(|(i,layer)| format!("layer {}\n{}", i, render_board(&Board(layer.clone()), &label)))
+            .collect();
+        side_by_side(&blocks, 2, width)
+    }
+
+    /// render_board for each of `boards`, side by side in `width` columns.
+    pub fn render_boards(boards: &[Board], label: impl Fn(usize) -> Option<String>, width: usize) -> String {
+        let blocks: Vec<String> = boards.iter().map(|board| render_board(board, &label)).collect();
+        side_by_side(&blocks, 2, width)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn two_copies_of_a_piece() {
+            let board = Board(vec![vec![0, 0, 1, 1, 1],
+                                   vec![0, 0, 0, 1, 1]]);
+            let labels = ["P", "P"].map(String::from);
+            let drawing = render_board(&board, |value| labels.get(value).cloned());
+            assert_eq!(drawing, "\
+┌───────┬───────────┐
+│ P   P │ P   P   P │
+│       └───┐       │
+│ P   P   P │ P   P │
+└───────────┴───────┘
+");
+        }
+
+        #[test]
+        fn holes_and_wide_labels() {
+            let board = Board(vec![vec![0, 0, 2],
+                                   vec![1, 0, 2],
+                                   vec![1, 1, 1]]);
+            let labels = ["F'", "L4"].map(String::from);
+            let drawing = render_board(&board, |value| labels.get(value).cloned());
+            // The holes in the top right corner are left out of the outline.
+            assert_eq!(drawing, "\
+┌─────────┐
+│ F'   F' │
+├────┐    │
+│ L4 │ F' │
+│    └────┴────┐
+│ L4   L4   L4 │
+└──────────────┘
+");
+            let blocks = [drawing.clone(), drawing.clone()];
+            assert_eq!(side_by_side(&blocks, 1, 80), "\
+┌─────────┐      ┌─────────┐
+│ F'   F' │      │ F'   F' │
+├────┐    │      ├────┐    │
+│ L4 │ F' │      │ L4 │ F' │
+│    └────┴────┐ │    └────┴────┐
+│ L4   L4   L4 │ │ L4   L4   L4 │
+└──────────────┘ └──────────────┘
+");
+            // Too narrow for both, so one below the other.
+            assert_eq!(side_by_side(&blocks, 1, 20), format!("{drawing}\n{drawing}"));
+        }
+
+        #[test]
+        fn cube_layers() {
+            let cube = Cube(vec![vec![vec![0, 0], vec![1, 2]],
+                                 vec![vec![0, 1], vec![1, 1]]]);
+            let labels = ["V", "T"].map(String::from);
+            let drawing = render_cube(&cube, |value| labels.get(value).cloned(), 80);
+            // The hole in layer 0 is left blank.
+            assert_eq!(drawing, "\
+layer 0    layer 1
+┌───────┐  ┌───┬───┐
+│ V   V │  │ V │ T │
+├───┬───┘  ├───┘   │
+│ T │      │ T   T │
+└───┘      └───────┘
+");
+            // Too narrow for both layers.
+            let narrow = render_cube(&cube, |value| labels.get(value).cloned(), 12);
+            assert_eq!(narrow.lines().filter(|line| line.starts_with("layer")).count(), 2);
+            assert!(narrow.contains("layer 0\n┌───────┐\n"));
+        }
+    }
+}
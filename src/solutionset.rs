@@ -3,6 +3,46 @@ pub mod solutionset {
 
     pub trait Transformable: Clone + Eq + std::hash::Hash {
         fn get_all_transformations(&self) -> Vec<Self>;
+
+        /// The transformations of get_all_transformations that do not mirror.
+        fn get_rotations(&self) -> Vec<Self>;
+
+        /// The transformations of get_all_transformations that map self onto
+        /// itself, each one given as a permutation of the row-major cell indices.
+        /// The identity is always among them.
+        fn get_symmetries(&self) -> Vec<Vec<usize>>;
+    }
+
+    /// Number of solutions found, with and without the congruent copies.
+    #[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+    pub struct SolutionCount {
+        pub raw: usize,
+        pub distinct: usize,
+    }
+
+    /// Counts solutions without keeping them around. By Burnside's lemma, the
+    /// number of classes of congruent solutions is the sum of the stabilizer
+    /// sizes of all solutions divided by the order of the symmetry group.
+    pub struct SolutionCounter {
+        raw: usize,
+        stabilizers: usize,
+        group_order: usize,
+    }
+
+    impl SolutionCounter {
+        pub fn new(group_order: usize) -> Self {
+            SolutionCounter { raw: 0, stabilizers: 0, group_order: group_order.max(1) }
+        }
+
+        /// `stabilizer` is the number of symmetries that map the solution onto itself.
+        pub fn add(&mut self, stabilizer: usize) {
+            self.raw += 1;
+            self.stabilizers += stabilizer;
+        }
+
+        pub fn get_count(&self) -> SolutionCount {
+            SolutionCount { raw: self.raw, distinct: self.stabilizers / self.group_order }
+        }
     }
     
     pub struct SolutionSet<T: Transformable> {
@@ -22,17 +62,34 @@ pub mod solutionset {
             self.solutions.len()
         }
 
-        pub fn add_solution(&mut self, item: T) {
-            if !self.congruent_solutions.contains(&item) {
-                self.solutions.push(item.clone());
-                for transformation in item.get_all_transformations() {
-                    self.congruent_solutions.insert(transformation);
-                }
+        pub fn is_empty(&self) -> bool {
+            self.solutions.is_empty()
+        }
+
+        /// Returns true if `item` was not congruent to any solution seen before.
+        pub fn add_solution(&mut self, item: T) -> bool {
+            if self.congruent_solutions.contains(&item) {
+                return false;
+            }
+            self.solutions.push(item.clone());
+            for transformation in item.get_all_transformations() {
+                self.congruent_solutions.insert(transformation);
             }
+            true
+        }
+
+        pub fn last(&self) -> Option<&T> {
+            self.solutions.last()
         }
 
         pub fn get_solutions(&self) -> Vec<T> {
             self.solutions.clone()
         }
     }
+
+    impl<T: Transformable> Default for SolutionSet<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
 }
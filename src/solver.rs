@@ -0,0 +1,551 @@
+pub mod solver {
+    use crate::pieceset::pieceset::IntoPieceSet;
+    use crate::dancinglinks::dlx::*;
+    use crate::board::board::*;
+    use crate::solutionset::solutionset::*;
+    use crate::cube::cube::*;
+    use crate::error::error::SolverError;
+    use crate::validate::validate::*;
+    use crate::symmetry::symmetry::*;
+    use std::collections::{HashMap, HashSet};
+    use std::ops::ControlFlow;
+
+    /// Dead-region pruning in the backtracking solver. After a piece is placed,
+    /// the open cells next to it are checked before the search goes deeper.
+    #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+    pub enum Pruning {
+        Off,
+        /// Give up if an open cell is left without open neighbors.
+        IsolatedCells,
+        /// Also give up if the piece cuts off a region of open cells whose size is
+        /// not a multiple of the piece size, see PieceSet::region_unit.
+        Regions,
+    }
+
+    /// Knobs shared by the solve_polyXXXX_XXX entry points.
+    #[derive(Clone,Debug)]
+    pub struct SolveOptions {
+        /// Stop after this many solutions. 0 means find all of them.
+        pub num_solutions: usize,
+        /// Rule out the symmetric copies of the solutions while building the
+        /// exact cover matrix instead of filtering them afterwards.
+        pub symmetry_breaking: bool,
+        /// Only used by the backtracking solver.
+        pub pruning: Pruning,
+        /// Number of threads to search with. 0 means one per available core.
+        pub num_threads: usize,
+        /// How many copies of each piece the puzzle uses, keyed by the index of
+        /// the piece in the piece list. Pieces missing from the map are left out.
+        /// None means one of each.
+        pub piece_counts: Option<HashMap<usize, usize>>,
+    }
+
+    impl SolveOptions {
+        /// Counting every solution is impractical from hexominoes on, so only
+        /// the first one is searched for by default. For a set of several
+        /// sizes, n is the largest one.
+        pub fn for_size(n: usize) -> Self {
+            SolveOptions { num_solutions: if n >= 6 { 1 } else { 0 }, symmetry_breaking: true,
+                           pruning: Pruning::Off, num_threads: 1, piece_counts: None }
+        }
+
+        /// num_threads with 0 resolved to the number of available cores.
+        pub fn threads(&self) -> usize {
+            match self.num_threads {
+                0 => std::thread::available_parallelism().map_or(1, |k| k.get()),
+                k => k,
+            }
+        }
+
+        /// piece_counts as the number of copies of each of the `num_pieces` pieces.
+        pub fn copies(&self, num_pieces: usize) -> Result<Vec<usize>, SolverError> {
+            let Some(piece_counts) = &self.piece_counts else {
+                return Ok(vec![1; num_pieces]);
+            };
+            let mut copies = vec![0; num_pieces];
+            for (&index,&count) in piece_counts {
+                *copies.get_mut(index).ok_or(SolverError::UnknownPiece { index, num_pieces })? = count;
+            }
+            Ok(copies)
+        }
+
+        /// The piece kind of each value that the copies of the pieces hold in a
+        /// solution: the copies of piece 0 first, then those of piece 1 and so
+        /// on. Holes hold the value after the last copy, the length of the list.
+        pub fn instance_kinds(&self, num_pieces: usize) -> Result<Vec<usize>, SolverError> {
+            Ok(self.copies(num_pieces)?.iter()
+                .enumerate()
+                .flat_map(|(k,&count)| std::iter::repeat_n(k, count))
+                .collect())
+        }
+    }
+
+    fn have_common_position(positions_a: &[usize], positions_b: &[usize]) -> bool {
+        positions_b.iter().any(|pos| positions_a.contains(pos))
+    }
+
+    pub fn pieces2positions(board: &[Vec<usize>], pieces: impl IntoPieceSet)
+                            -> Result<(Vec<Vec<usize>>,Vec<usize>), SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let num_pieces = pieces.len();
+        let mut positions: Vec<Vec<usize>> = Vec::new();
+        let mut kinds: Vec<usize> = Vec::new();
+
+        let (board_h,board_w) = board_shape(board)?;
+        let mut hole_positions = Vec::new();
+        for (i,row) in board.iter().enumerate() {
+            for (j,value) in row.iter().enumerate() {
+                if *value != 0 {
+                    hole_positions.push(i*board_w+j);
+                }
+            }
+        }
+        for (k,congruent_figures) in pieces.orientations().iter().enumerate() {
+            for figure in congruent_figures {
+                let (figure_h,figure_w) = figure.iter().fold((usize::MIN,usize::MIN),
+                                                             |(max_i,max_j),&(i,j)| (max_i.max(i),max_j.max(j)));
+                for offset_i in 0..board_h.saturating_sub(figure_h) {
+                    for offset_j in 0..board_w.saturating_sub(figure_w) {
+                        let mut figure_positions: Vec<_> = figure.iter()
+                            .map(|(i,j)| (i+offset_i)*board_w + (j+offset_j))
+                            .collect();
+                        if have_common_position(&figure_positions, &hole_positions) {
+                            continue;
+                        } else {
+                            figure_positions.push(k + board_h*board_w);
+                            positions.push(figure_positions);
+                            kinds.push(k);
+                        }
+                    }
+                }
+            }
+        }
+
+        hole_positions.push(board_h*board_w + num_pieces);
+        positions.push(hole_positions);
+        kinds.push(num_pieces);
+
+        Ok((positions,kinds))
+    }
+
+    // The value of each row of `solution` on the board: the copies of a piece
+    // get consecutive values, as in SolveOptions::instance_kinds, in the order
+    // of the first cell they cover, and the holes the value after them.
+    fn instance_values(solution: &[usize], kinds: &[usize], positions: &[Vec<usize>], copies: &[usize])
+                       -> Vec<(usize,usize)> {
+        let mut next_value: Vec<usize> = copies.iter()
+            .scan(0, |first, &count| { *first += count; Some(*first - count) })
+            .collect();
+        next_value.push(copies.iter().sum());
+        let mut rows = solution.to_vec();
+        rows.sort_by_key(|&k| positions[k][..positions[k].len()-1].iter().min().copied());
+        rows.into_iter().map(|k| {
+            let value = next_value[kinds[k]];
+            next_value[kinds[k]] += 1;
+            (k,value)
+        }).collect()
+    }
+
+    /// The board of a solution. Every copy of a piece holds its own value, see
+    /// SolveOptions::instance_kinds for the piece it is a copy of.
+    pub fn solution2board(solution: &[usize],
+                          kinds: &[usize],
+                          positions: &[Vec<usize>],
+                          copies: &[usize],
+                          board: &[Vec<usize>]) -> Board {
+        let mut ret: Board = Board::new(board.len(), board.first().map_or(0, |row| row.len()));
+        for (k,value) in instance_values(solution, kinds, positions, copies) {
+            for pos in &positions[k] {
+                if *pos >= ret.height()*ret.width() {
+                    continue;
+                }
+                let a = pos / ret.width();
+                let b = pos % ret.width();
+                *ret.get_mut(a,b) = value;
+            }
+        }
+        ret
+    }
+
+    pub fn solve_polyomino_dlx(board: &[Vec<usize>], pieces: impl IntoPieceSet) -> Result<Vec<Board>, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        solve_polyomino_dlx_with(board, &pieces, &SolveOptions::for_size(pieces.max_size()))
+    }
+
+    pub fn solve_polyomino_dlx_with(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                                    -> Result<Vec<Board>, SolverError> {
+        let mut solutions = Vec::new();
+        visit_polyomino_dlx(board, pieces, options, |solution| {
+            solutions.push(solution.clone());
+            ControlFlow::Continue(())
+        })?;
+        Ok(solutions)
+    }
+
+    fn build_matrix(positions: &[Vec<usize>], bounds: &[(usize,usize)]) -> Matrix {
+        let mut m = Matrix::with_multiplicities(bounds);
+        for pos_1hvec in positions {
+            m.add_row(pos_1hvec);
+        }
+        m
+    }
+
+    // Drops the placements of the pieces that are not used and returns the
+    // column bounds of the matrix: every cell once, every piece as many times as
+    // it has copies, and the holes once.
+    fn apply_copies(positions: &mut Vec<Vec<usize>>, kinds: &mut Vec<usize>, num_cells: usize, copies: &[usize])
+                    -> Vec<(usize,usize)> {
+        let used = |k: usize| copies.get(k).is_none_or(|&count| count > 0);
+        let mut kinds_iter = kinds.iter();
+        positions.retain(|_| used(*kinds_iter.next().unwrap()));
+        kinds.retain(|&k| used(k));
+        let mut bounds = vec![(1,1); num_cells];
+        bounds.extend(copies.iter().map(|&count| if count > 0 { (count,count) } else { (0,1) }));
+        bounds.push((1,1));
+        bounds
+    }
+
+    // dlx::search, or dlx::par_search if `options` asks for more than one thread.
+    fn run_search<F>(m: &mut Matrix, options: &SolveOptions, visit: F)
+    where
+        F: FnMut(&[usize]) -> ControlFlow<()>,
+    {
+        match options.threads() {
+            1 => search(m, visit),
+            num_threads => par_search(m, num_threads, visit),
+        }
+    }
+
+    // Hands every solution of `m` that is not congruent to an earlier one to
+    // `visit` until options.num_solutions of them have been seen. The congruent
+    // ones are recognized by their canonical rows under `row_symmetries`, or not
+    // at all if the symmetries were broken in `m` and there are none to find.
+    // Rows rather than boards, since a symmetry can change which copy of a piece
+    // holds which value.
+    fn visit_distinct<T, F>(m: &mut Matrix,
+                            options: &SolveOptions,
+                            row_symmetries: Option<&[Vec<usize>]>,
+                            to_solution: impl Fn(&[usize]) -> T,
+                            mut visit: F)
+    where
+        F: FnMut(&T) -> ControlFlow<()>,
+    {
+        let mut seen = HashSet::new();
+        let mut num_found = 0;
+        run_search(m, options, |answer| {
+            if let Some(row_symmetries) = row_symmetries {
+                if !seen.insert(canonical(answer, row_symmetries)) {
+                    return ControlFlow::Continue(());
+                }
+            }
+            visit(&to_solution(answer))?;
+            num_found += 1;
+            if options.num_solutions > 0 && num_found >= options.num_solutions {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+    }
+
+    // Drops the rows that break_symmetries rules out and returns the order of the
+    // symmetry group, or leaves the rows alone and returns None.
+    fn break_row_symmetries(positions: &mut Vec<Vec<usize>>,
+                            kinds: &mut Vec<usize>,
+                            row_symmetries: &[Vec<usize>],
+                            copies: &[usize]) -> Option<usize> {
+        let SymmetryBreak { keep, group_order } = break_symmetries(kinds, row_symmetries, copies)?;
+        let mut keep_iter = keep.iter();
+        positions.retain(|_| *keep_iter.next().unwrap());
+        let mut keep_iter = keep.iter();
+        kinds.retain(|_| *keep_iter.next().unwrap());
+        Some(group_order)
+    }
+
+    fn count_distinct(m: &mut Matrix, row_symmetries: &[Vec<usize>], options: &SolveOptions) -> SolutionCount {
+        let mut counter = SolutionCounter::new(row_symmetries.len());
+        run_search(m, options, |answer| {
+            counter.add(stabilizer(answer, row_symmetries));
+            ControlFlow::Continue(())
+        });
+        counter.get_count()
+    }
+
+    // Counts the solutions made of `positions`, breaking the symmetries of the
+    // puzzle first if `options` allows it.
+    fn count_rows(mut positions: Vec<Vec<usize>>,
+                  mut kinds: Vec<usize>,
+                  num_cells: usize,
+                  copies: &[usize],
+                  symmetries: &[Vec<usize>],
+                  options: &SolveOptions) -> SolutionCount {
+        let bounds = apply_copies(&mut positions, &mut kinds, num_cells, copies);
+        let row_symmetries = placement_symmetries(&positions, num_cells, symmetries, copies);
+        if options.symmetry_breaking {
+            if let Some(group_order) = break_row_symmetries(&mut positions, &mut kinds, &row_symmetries, copies) {
+                let mut distinct = 0;
+                run_search(&mut build_matrix(&positions, &bounds), options, |_| {
+                    distinct += 1;
+                    ControlFlow::Continue(())
+                });
+                return SolutionCount { raw: distinct * group_order, distinct };
+            }
+        }
+        count_distinct(&mut build_matrix(&positions, &bounds), &row_symmetries, options)
+    }
+
+    /// Counts the solutions of a polyomino puzzle, all of them and up to
+    /// congruence, without building a Board for any of them. The solution limit
+    /// in `options` is ignored.
+    pub fn count_polyomino_dlx(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions)
+                               -> Result<SolutionCount, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (board_h,board_w) = validate_board(board, pieces.area(&copies), pieces.region_unit(&copies))?;
+        let (positions,kinds) = pieces2positions(board, pieces)?;
+        Ok(count_rows(positions, kinds, board_h * board_w, &copies, &Board(board.to_vec()).get_symmetries(), options))
+    }
+
+    /// Streams the non-congruent solutions of a polyomino puzzle to `visit` one at
+    /// a time, as the search finds them. Return `ControlFlow::Break` to stop early.
+    pub fn visit_polyomino_dlx<F>(board: &[Vec<usize>], pieces: impl IntoPieceSet, options: &SolveOptions, visit: F)
+                                  -> Result<(), SolverError>
+    where
+        F: FnMut(&Board) -> ControlFlow<()>,
+    {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (board_h,board_w) = validate_board(board, pieces.area(&copies), pieces.region_unit(&copies))?;
+        let (mut positions,mut kinds) = pieces2positions(board, pieces)?;
+        let num_cells = board_h * board_w;
+        let bounds = apply_copies(&mut positions, &mut kinds, num_cells, &copies);
+        let symmetries = Board(board.to_vec()).get_symmetries();
+        let row_symmetries = placement_symmetries(&positions, num_cells, &symmetries, &copies);
+        let symmetries_broken = options.symmetry_breaking
+            && break_row_symmetries(&mut positions, &mut kinds, &row_symmetries, &copies).is_some();
+        let mut m = build_matrix(&positions, &bounds);
+        visit_distinct(&mut m, options, (!symmetries_broken).then_some(&row_symmetries[..]),
+                       |answer| solution2board(answer, &kinds, &positions, &copies, board),
+                       visit);
+        Ok(())
+    }
+
+    pub fn pieces2positions3d(cube: &[Vec<Vec<usize>>], pieces: impl IntoPieceSet<(usize,usize,usize)>)
+                              -> Result<(Vec<Vec<usize>>,Vec<usize>), SolverError> {
+        let (dim0,dim1,dim2) = cube_shape(cube)?;
+
+        let pieces = pieces.into_piece_set()?;
+        let num_pieces = pieces.len();
+        let mut positions: Vec<Vec<usize>> = Vec::new();
+        let mut kinds: Vec<usize> = Vec::new();
+        let mut hole_positions = Vec::new();
+        for (i,plane) in cube.iter().enumerate() {
+            for (j,row) in plane.iter().enumerate() {
+                for (k,value) in row.iter().enumerate() {
+                    if *value != 0 {
+                        hole_positions.push(i*dim1*dim2 + j*dim2 + k);
+                    }
+                }
+            }
+        }
+        for (kind,congruent_figures) in pieces.orientations().iter().enumerate() {
+            for figure in congruent_figures {
+                let (figure_dim0,figure_dim1,figure_dim2) = figure.iter()
+                    .fold((usize::MIN,usize::MIN,usize::MIN),
+                          |(max_i,max_j,max_k),&(i,j,k)| (max_i.max(i),max_j.max(j),max_k.max(k)));
+                for offset_i in 0..dim0.saturating_sub(figure_dim0) {
+                    for offset_j in 0..dim1.saturating_sub(figure_dim1) {
+                        for offset_k in 0..dim2.saturating_sub(figure_dim2) {
+                            let mut figure_positions: Vec<_> = figure.iter()
+                                .map(|(i,j,k)| (i+offset_i)*dim1*dim2 + (j+offset_j)*dim2 + (k+offset_k))
+                                .collect();
+                            if have_common_position(&figure_positions, &hole_positions) {
+                                continue;
+                            } else {
+                                figure_positions.push(kind + dim0*dim1*dim2);
+                                positions.push(figure_positions);
+                                kinds.push(kind);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        hole_positions.push(dim0*dim1*dim2 + num_pieces);
+        positions.push(hole_positions);
+        kinds.push(num_pieces);
+
+        Ok((positions,kinds))
+    }
+
+    /// Same as solution2board for polycube puzzles.
+    pub fn solution2cube(solution: &[usize],
+                         kinds: &[usize],
+                         positions: &[Vec<usize>],
+                         copies: &[usize],
+                         cube: &[Vec<Vec<usize>>]) -> Cube {
+        let dim0 = cube.len();
+        let dim1 = cube.first().map_or(0, |plane| plane.len());
+        let dim2 = cube.first().and_then(|plane| plane.first()).map_or(0, |row| row.len());
+        let mut ret: Cube = Cube::new(dim0,dim1,dim2);
+        for (k,value) in instance_values(solution, kinds, positions, copies) {
+            for pos in &positions[k] {
+                if *pos >= dim0*dim1*dim2 {
+                    continue;
+                }
+                let a = pos / (dim1*dim2);
+                let remainder = pos % (dim1*dim2);
+                let b = remainder / dim2;
+                let c = remainder % dim2;
+                *ret.get_mut(a,b,c) = value;
+            }
+        }
+        ret
+    }
+
+    pub fn solve_polycube_dlx(cube: &[Vec<Vec<usize>>], pieces: impl IntoPieceSet<(usize,usize,usize)>)
+                              -> Result<Vec<Cube>, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let options = SolveOptions { num_solutions: 0, ..SolveOptions::for_size(pieces.max_size()) };
+        solve_polycube_dlx_with(cube, pieces, &options)
+    }
+
+    pub fn solve_polycube_dlx_with(cube: &[Vec<Vec<usize>>],
+                                   pieces: impl IntoPieceSet<(usize,usize,usize)>,
+                                   options: &SolveOptions) -> Result<Vec<Cube>, SolverError> {
+        let mut solutions = Vec::new();
+        visit_polycube_dlx(cube, pieces, options, |solution| {
+            solutions.push(solution.clone());
+            ControlFlow::Continue(())
+        })?;
+        Ok(solutions)
+    }
+
+    /// Same as count_polyomino_dlx for polycube puzzles.
+    pub fn count_polycube_dlx(cube: &[Vec<Vec<usize>>],
+                              pieces: impl IntoPieceSet<(usize,usize,usize)>,
+                              options: &SolveOptions) -> Result<SolutionCount, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (dim0,dim1,dim2) = validate_cube(cube, pieces.area(&copies), pieces.region_unit(&copies))?;
+        let (positions,kinds) = pieces2positions3d(cube, pieces)?;
+        Ok(count_rows(positions, kinds, dim0 * dim1 * dim2, &copies, &Cube(cube.to_vec()).get_symmetries(), options))
+    }
+
+    /// Same as visit_polyomino_dlx for polycube puzzles.
+    pub fn visit_polycube_dlx<F>(cube: &[Vec<Vec<usize>>],
+                                 pieces: impl IntoPieceSet<(usize,usize,usize)>,
+                                 options: &SolveOptions,
+                                 visit: F) -> Result<(), SolverError>
+    where
+        F: FnMut(&Cube) -> ControlFlow<()>,
+    {
+        let pieces = pieces.into_piece_set()?;
+        let copies = options.copies(pieces.len())?;
+        let (dim0,dim1,dim2) = validate_cube(cube, pieces.area(&copies), pieces.region_unit(&copies))?;
+        let (mut positions,mut kinds) = pieces2positions3d(cube, pieces)?;
+        let num_cells = dim0 * dim1 * dim2;
+        let bounds = apply_copies(&mut positions, &mut kinds, num_cells, &copies);
+        let symmetries = Cube(cube.to_vec()).get_symmetries();
+        let row_symmetries = placement_symmetries(&positions, num_cells, &symmetries, &copies);
+        let symmetries_broken = options.symmetry_breaking
+            && break_row_symmetries(&mut positions, &mut kinds, &row_symmetries, &copies).is_some();
+        let mut m = build_matrix(&positions, &bounds);
+        visit_distinct(&mut m, options, (!symmetries_broken).then_some(&row_symmetries[..]),
+                       |answer| solution2cube(answer, &kinds, &positions, &copies, cube),
+                       visit);
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::backtracking::backtracking::{count_polyomino_bt, solve_polyomino_bt_with};
+        use crate::pieceset::pieceset::PieceSet;
+        use crate::testset::testset::test_board;
+
+        #[test]
+        fn two_of_each_tetromino() {
+            let board = vec![vec![0; 10]; 4];
+            let mut options = SolveOptions::for_size(4);
+            options.piece_counts = Some((0..5).map(|k| (k, 2)).collect());
+            let expected = (449, 1796);
+            let count = count_polyomino_dlx(&board, 4, &options).unwrap();
+            assert_eq!((count.distinct, count.raw), expected);
+            let count = count_polyomino_bt(&board, 4, &options).unwrap();
+            assert_eq!((count.distinct, count.raw), expected);
+            options.symmetry_breaking = false;
+            let count = count_polyomino_dlx(&board, 4, &options).unwrap();
+            assert_eq!((count.distinct, count.raw), expected);
+        }
+
+        #[test]
+        fn unknown_piece_in_counts() {
+            let board = vec![vec![0; 4]; 2];
+            let mut options = SolveOptions::for_size(4);
+            options.piece_counts = Some(HashMap::from([(5, 2)]));
+            assert!(matches!(count_polyomino_dlx(&board, 4, &options),
+                             Err(SolverError::UnknownPiece { index: 5, num_pieces: 5 })));
+        }
+
+        #[test]
+        fn threaded_dlx() {
+            let board = vec![vec![0; 10]; 6];
+            let single = SolveOptions::for_size(5);
+            let threaded = SolveOptions { num_threads: 4, ..SolveOptions::for_size(5) };
+            let solutions = |options: &SolveOptions| -> HashSet<Board> {
+                solve_polyomino_dlx_with(&board, 5, options).unwrap().into_iter().collect()
+            };
+            let expected = solutions(&single);
+            assert_eq!(expected.len(), 2339);
+            assert!(solutions(&threaded) == expected);
+            let count = count_polyomino_dlx(&board, 5, &threaded).unwrap();
+            assert_eq!((count.distinct, count.raw), (2339, 9356));
+
+            // Without symmetry breaking, congruent solutions from different threads must still be merged.
+            let board = test_board("501").unwrap();
+            let single = SolveOptions { symmetry_breaking: false, ..single };
+            let threaded = SolveOptions { symmetry_breaking: false, ..threaded };
+            assert_eq!(solve_polyomino_dlx_with(&board, 5, &threaded).unwrap().len(), 65);
+            let (single, threaded) = (count_polyomino_dlx(&board, 5, &single).unwrap(),
+                                      count_polyomino_dlx(&board, 5, &threaded).unwrap());
+            assert_eq!((threaded.distinct, threaded.raw), (single.distinct, single.raw));
+        }
+
+        #[test]
+        fn copies_hold_their_own_values() {
+            // Two P pentominoes on a 2x5 board, and the 8x8 board with a centre
+            // hole, whose holes hold the value after the last piece.
+            let board = vec![vec![0; 5]; 2];
+            let options = SolveOptions { piece_counts: Some(HashMap::from([(5, 2)])), ..SolveOptions::for_size(5) };
+            assert_eq!(options.instance_kinds(12).unwrap(), vec![5, 5]);
+            let expected = vec![vec![0, 0, 1, 1, 1], vec![0, 0, 0, 1, 1]];
+            assert_eq!(solve_polyomino_dlx_with(&board, 5, &options).unwrap()[0].0, expected);
+            assert_eq!(solve_polyomino_bt_with(&board, 5, &options).unwrap()[0].0, expected);
+
+            let board = test_board("501").unwrap();
+            let options = SolveOptions { num_solutions: 1, ..SolveOptions::for_size(5) };
+            assert_eq!(options.instance_kinds(12).unwrap(), (0..12).collect::<Vec<_>>());
+            for solution in [solve_polyomino_dlx_with(&board, 5, &options).unwrap(),
+                             solve_polyomino_bt_with(&board, 5, &options).unwrap()] {
+                assert_eq!(solution[0].0[3][3..5], [12, 12]);
+                assert_eq!(solution[0].0[4][3..5], [12, 12]);
+            }
+        }
+
+        #[test]
+        fn rejected_inputs() {
+            let options = SolveOptions::for_size(5);
+            let board = vec![vec![0; 5]; 2];
+            assert_eq!(count_polyomino_dlx(&board, 0, &options).err(), Some(SolverError::UnsupportedSize(0)));
+            assert_eq!(count_polyomino_dlx(&[], 5, &options).err(), Some(SolverError::EmptyBoard));
+            assert_eq!(count_polyomino_dlx(&board, 5, &options).err(),
+                       Some(SolverError::AreaMismatch { open_cells: 10, piece_area: 60 }));
+            let pieces: Result<PieceSet, _> = PieceSet::new(vec![vec![(0,0)], vec![]]);
+            assert_eq!(pieces.err(), Some(SolverError::EmptyPiece(1)));
+            let pieces: Result<PieceSet, _> = PieceSet::named(&["Q"]);
+            assert_eq!(pieces.err(), Some(SolverError::UnknownPieceName("Q".to_string())));
+            assert_eq!(test_board("nope").err(), Some(SolverError::UnknownTestName("nope".to_string())));
+        }
+    }
+}
@@ -0,0 +1,388 @@
+pub mod svg {
+    use std::collections::HashMap;
+    use crate::board::board::Board;
+    use crate::cube::cube::Cube;
+    use crate::palette::palette::{board_colors, cube_colors, HOLE_COLOR, PALETTE};
+
+    /// How board_svg, sheet_svg and their cube counterparts draw. Lengths are in
+    /// pixels.
+    #[derive(Clone,Debug)]
+    pub struct SvgOptions {
+        /// Side of a cell.
+        pub cell_size: usize,
+        /// Fill colors of the pieces, given out so that pieces that touch have
+        /// different ones, see palette::board_colors.
+        pub palette: Vec<(u8,u8,u8)>,
+        /// Fill color of the holes.
+        pub hole_color: (u8,u8,u8),
+        /// Width of the outlines of the pieces and holes.
+        pub stroke_width: usize,
+        /// Write the label of every piece in one of its cells.
+        pub labels: bool,
+        /// Number of boards on a row of a sheet.
+        pub columns: usize,
+        /// Space between the boards of a sheet, and between the layers of a cube.
+        pub gap: usize,
+    }
+
+    impl Default for SvgOptions {
+        fn default() -> Self {
+            SvgOptions { cell_size: 30, palette: PALETTE.to_vec(), hole_color: HOLE_COLOR,
+                         stroke_width: 2, labels: true, columns: 4, gap: 30 }
+        }
+    }
+
+    fn hex((r,g,b): (u8,u8,u8)) -> String {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    // The outline of the cells for which `inside` holds, as closed loops of
+    // grid points (x,y), with the points in the middle of straight runs left out.
+    fn outline(h: usize, w: usize, inside: impl Fn(usize, usize) -> bool) -> Vec<Vec<(usize,usize)>> {
+        // Every edge of a cell that borders a cell outside, clockwise around it.
+        let mut edges: HashMap<(usize,usize), Vec<(usize,usize)>> = HashMap::new();
+        for i in 0..h {
+            for j in 0..w {
+                if !inside(i, j) {
+                    continue;
+                }
+                if i == 0 || !inside(i-1, j) {
+                    edges.entry((j,i)).or_default().push((j+1,i));
+                }
+                if j + 1 == w || !inside(i, j+1) {
+                    edges.entry((j+1,i)).or_default().push((j+1,i+1));
+                }
+                if i + 1 == h || !inside(i+1, j) {
+                    edges.entry((j+1,i+1)).or_default().push((j,i+1));
+                }
+                if j == 0 || !inside(i, j-1) {
+                    edges.entry((j,i+1)).or_default().push((j,i));
+                }
+            }
+        }
+
+        let mut loops = Vec::new();
+        let mut starts: Vec<(usize,usize)> = edges.keys().copied().collect();
+        starts.sort();
+        for start in starts {
+            while edges.get(&start).is_some_and(|ends| !ends.is_empty()) {
+                let mut points = vec![start];
+                let mut point = start;
+                while let Some(next) = edges.get_mut(&point).and_then(|ends| ends.pop()) {
+                    point = next;
+                    if point == start {
+                        break;
+                    }
+                    points.push(point);
+                }
+                let n = points.len();
+                let corners = (0..n)
+                    .filter(|&k| {
+                        let (prev,next) = (points[(k + n - 1) % n], points[(k + 1) % n]);
+                        !(prev.0 == next.0 || prev.1 == next.1)
+                    })
+                    .map(|k| points[k])
+                    .collect();
+                loops.push(corners);
+            }
+        }
+        loops
+    }
+
+    // An SVG path through the loops, with the grid point (x,y) at
+    // (x0 + x*cell, y0 + y*cell).
+    fn path(loops: &[Vec<(usize,usize)>], x0: usize, y0: usize, cell: usize) -> String {
+        loops.iter()
+            .map(|points| {
+                let coords: Vec<String> = points.iter()
+                    .map(|&(x,y)| format!("{},{}", x0 + x * cell, y0 + y * cell))
+                    .collect();
+                format!("M{}Z", coords.join(" L"))
+            })
+            .collect()
+    }
+
+    // The pieces and holes of `board` with its top left corner at (x0,y0).
+    fn board_elements(board: &Board, label: &impl Fn(usize) -> Option<String>,
+                      options: &SvgOptions, x0: usize, y0: usize) -> String {
+        let (h,w) = (board.height(), board.width());
+        let cell = options.cell_size;
+        let labels: Vec<Vec<Option<String>>> = board.0.iter()
+            .map(|row| row.iter().map(|&value| label(value)).collect())
+            .collect();
+        let is_hole = |i: usize, j: usize| labels[i][j].is_none();
+        let colors = board_colors(board, &options.palette, |value| label(value).is_some());
+        let mut values: Vec<usize> = colors.keys().copied().collect();
+        values.sort();
+
+        let mut out = String::new();
+        let stroke = format!("stroke=\"#000000\" stroke-width=\"{}\" stroke-linejoin=\"round\"", options.stroke_width);
+        let holes = outline(h, w, is_hole);
+        if !holes.is_empty() {
+            out.push_str(&format!("<path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\" {}/>\n",
+                                  path(&holes, x0, y0, cell), hex(options.hole_color), stroke));
+        }
+        for &value in &values {
+            let inside = |i: usize, j: usize| board.0[i][j] == value && !is_hole(i, j);
+            let loops = outline(h, w, inside);
+            if loops.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("<path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\" {}/>\n",
+                                  path(&loops, x0, y0, cell), hex(colors[&value]), stroke));
+            if !options.labels {
+                continue;
+            }
+            // The label goes in the cell of the piece nearest to its center.
+            let cells: Vec<(usize,usize)> = (0..h).flat_map(|i| (0..w).map(move |j| (i,j)))
+                .filter(|&(i,j)| inside(i, j))
+                .collect();
+            let (ci,cj) = cells.iter().fold((0.0,0.0), |(ci,cj),&(i,j)| (ci + i as f64, cj + j as f64));
+            let (ci,cj) = (ci / cells.len() as f64, cj / cells.len() as f64);
+            let distance = |&(i,j): &(usize,usize)| (i as f64 - ci).powi(2) + (j as f64 - cj).powi(2);
+            let Some(&(i,j)) = cells.iter().min_by(|a,b| distance(a).total_cmp(&distance(b))) else {
+                continue;
+            };
+            let text = labels[i][j].as_deref().unwrap_or("");
+            out.push_str(&format!("<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" \
+                                   text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                                  x0 + j * cell + cell / 2, y0 + i * cell + cell / 2, cell / 2, escape(text)));
+        }
+        out
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+
+    fn document(width: usize, height: usize, body: &str) -> String {
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+                 viewBox=\"0 0 {w} {h}\">\n{}</svg>\n", body, w = width, h = height)
+    }
+
+    /// An SVG image of `board`, every piece a filled polygon with its outline
+    /// and the holes filled with options.hole_color. `label` maps the value of
+    /// a cell to the label of its piece, or to None for a hole, as for
+    /// render::render_board, so every copy of a piece is a polygon of its own.
+    pub fn board_svg(board: &Board, label: impl Fn(usize) -> Option<String>, options: &SvgOptions) -> String {
+        let margin = options.stroke_width;
+        let body = board_elements(board, &label, options, margin, margin);
+        document(board.width() * options.cell_size + 2 * margin, board.height() * options.cell_size + 2 * margin, &body)
+    }
+
+    /// An SVG image of all the `boards`, such as the solutions of
+    /// SolutionSet::get_solutions, drawn as by board_svg on a grid of
+    /// options.columns boards per row.
+    pub fn sheet_svg(boards: &[Board], label: impl Fn(usize) -> Option<String>, options: &SvgOptions) -> String {
+        let columns = options.columns.max(1);
+        let board_w = boards.iter().map(|board| board.width()).max().unwrap_or(0) * options.cell_size;
+        let board_h = boards.iter().map(|board| board.height()).max().unwrap_or(0) * options.cell_size;
+        let rows = boards.len().div_ceil(columns);
+        let margin = options.stroke_width;
+        let mut body = String::new();
+        for (k,board) in boards.iter().enumerate() {
+            let x0 = margin + (k % columns) * (board_w + options.gap);
+            let y0 = margin + (k / columns) * (board_h + options.gap);
+            body.push_str(&board_elements(board, &label, options, x0, y0));
+        }
+        let width = columns.min(boards.len()) * (board_w + options.gap);
+        let height = rows * (board_h + options.gap);
+        document(width.saturating_sub(options.gap) + 2 * margin, height.saturating_sub(options.gap) + 2 * margin, &body)
+    }
+
+    fn shade((r,g,b): (u8,u8,u8), factor: f64) -> (u8,u8,u8) {
+        let dim = |c: u8| (c as f64 * factor).round() as u8;
+        (dim(r), dim(g), dim(b))
+    }
+
+    // The width and height of the isometric view of `cube` drawn by cube_elements.
+    fn cube_extent(cube: &Cube, options: &SvgOptions) -> (f64,f64) {
+        let cell = options.cell_size as f64;
+        let layers = cube.0.len();
+        let rows = cube.0.first().map_or(0, |layer| layer.len());
+        let columns = cube.0.first().and_then(|layer| layer.first()).map_or(0, |row| row.len());
+        let height = layers as f64 * cell + layers.saturating_sub(1) as f64 * options.gap as f64;
+        let across = (rows + columns) as f64;
+        (across * cell * 3f64.sqrt() / 2.0, across * cell / 2.0 + height)
+    }
+
+    // The isometric view of `cube` with the top left corner of its extent at
+    // (x0,y0). The layers are pulled options.gap apart, layer 0 on top, so that
+    // the top face of every unit cube shows.
+    fn cube_elements(cube: &Cube, label: &impl Fn(usize) -> Option<String>,
+                     options: &SvgOptions, x0: f64, y0: f64) -> String {
+        let cell = options.cell_size as f64;
+        let layers = cube.0.len();
+        let rows = cube.0.first().map_or(0, |layer| layer.len());
+        // The height of the stack of layers, at which the back corner of the top face of layer 0 is.
+        let height = layers as f64 * cell + layers.saturating_sub(1) as f64 * options.gap as f64;
+        let cos = 3f64.sqrt() / 2.0;
+        // The screen position of the point (x,y) of the grid at height z.
+        let point = |x: usize, y: usize, z: f64| {
+            let (x,y) = (x as f64, y as f64);
+            format!("{:.1},{:.1}", x0 + (rows as f64 + x - y) * cos * cell, y0 + height - z + (x + y) * cell / 2.0)
+        };
+        let colors = cube_colors(cube, &options.palette, |value| label(value).is_some());
+        let solid = |i: usize, j: usize, k: usize| {
+            cube.0[i].get(j).and_then(|row| row.get(k)).is_some_and(|&value| label(value).is_some())
+        };
+        let stroke = format!("stroke=\"#000000\" stroke-width=\"{}\" stroke-linejoin=\"round\"", options.stroke_width);
+
+        // The bottom layer first, and within a layer from the back to the front.
+        let mut cells: Vec<(usize,usize,usize)> = Vec::new();
+        for i in (0..layers).rev() {
+            let mut layer: Vec<(usize,usize,usize)> = (0..rows)
+                .flat_map(|j| (0..cube.0[i][j].len()).map(move |k| (i,j,k)))
+                .filter(|&(i,j,k)| solid(i, j, k))
+                .collect();
+            layer.sort_by_key(|&(_,j,k)| (j + k, j));
+            cells.extend(layer);
+        }
+
+        let mut out = String::new();
+        for (i,j,k) in cells {
+            let value = cube.0[i][j][k];
+            let color = colors[&value];
+            let z = (layers - 1 - i) as f64 * (cell + options.gap as f64);
+            let mut faces = Vec::new();
+            if !solid(i, j, k+1) {
+                faces.push(([point(k+1,j,z), point(k+1,j+1,z), point(k+1,j+1,z+cell), point(k+1,j,z+cell)], 0.6));
+            }
+            if !solid(i, j+1, k) {
+                faces.push(([point(k,j+1,z), point(k+1,j+1,z), point(k+1,j+1,z+cell), point(k,j+1,z+cell)], 0.8));
+            }
+            let top = z + cell;
+            faces.push(([point(k,j,top), point(k+1,j,top), point(k+1,j+1,top), point(k,j+1,top)], 1.0));
+            for (corners,factor) in faces {
+                out.push_str(&format!("<path d=\"M{}Z\" fill=\"{}\" {}/>\n",
+                                      corners.join(" L"), hex(shade(color, factor)), stroke));
+            }
+            if options.labels {
+                let text = label(value).unwrap_or_default();
+                let (x,y) = (k as f64 + 0.5, j as f64 + 0.5);
+                out.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"{}\" \
+                                       text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                                      x0 + (rows as f64 + x - y) * cos * cell, y0 + height - top + (x + y) * cell / 2.0,
+                                      options.cell_size / 3, escape(&text)));
+            }
+        }
+        out
+    }
+
+    /// An isometric SVG image of `cube`, seen from above with its layers pulled
+    /// apart, layer 0 on top as Cube::pprint and render::render_cube list it.
+    /// Every unit cube is drawn with the color of its piece, its sides darker,
+    /// and the label of the piece on its top face. `label` is as for board_svg;
+    /// holes are left out.
+    pub fn cube_svg(cube: &Cube, label: impl Fn(usize) -> Option<String>, options: &SvgOptions) -> String {
+        let margin = options.stroke_width as f64;
+        let (width,height) = cube_extent(cube, options);
+        let body = cube_elements(cube, &label, options, margin, margin);
+        document((width + 2.0 * margin).ceil() as usize, (height + 2.0 * margin).ceil() as usize, &body)
+    }
+
+    /// An SVG image of all the `cubes` drawn as by cube_svg, on a grid of
+    /// options.columns cubes per row.
+    pub fn cube_sheet_svg(cubes: &[Cube], label: impl Fn(usize) -> Option<String>, options: &SvgOptions) -> String {
+        let columns = options.columns.max(1);
+        let extents: Vec<(f64,f64)> = cubes.iter().map(|cube| cube_extent(cube, options)).collect();
+        let cube_w = extents.iter().map(|&(w,_)| w).fold(0.0, f64::max);
+        let cube_h = extents.iter().map(|&(_,h)| h).fold(0.0, f64::max);
+        let rows = cubes.len().div_ceil(columns);
+        let (margin,gap) = (options.stroke_width as f64, options.gap as f64);
+        let mut body = String::new();
+        for (k,cube) in cubes.iter().enumerate() {
+            let x0 = margin + (k % columns) as f64 * (cube_w + gap);
+            let y0 = margin + (k / columns) as f64 * (cube_h + gap);
+            body.push_str(&cube_elements(cube, &label, options, x0, y0));
+        }
+        let width = (columns.min(cubes.len()) as f64 * (cube_w + gap) - gap).max(0.0) + 2.0 * margin;
+        let height = (rows as f64 * (cube_h + gap) - gap).max(0.0) + 2.0 * margin;
+        document(width.ceil() as usize, height.ceil() as usize, &body)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn options() -> SvgOptions {
+            SvgOptions { cell_size: 10, stroke_width: 1, ..SvgOptions::default() }
+        }
+
+        // The elements that board_elements writes with options().
+        fn polygon(d: &str, fill: &str) -> String {
+            format!("<path d=\"{d}\" fill=\"{fill}\" fill-rule=\"evenodd\" \
+                     stroke=\"#000000\" stroke-width=\"1\" stroke-linejoin=\"round\"/>\n")
+        }
+
+        // A label in `size` pixels, at x and y as written out.
+        fn text(x: impl std::fmt::Display, y: impl std::fmt::Display, size: usize, label: &str) -> String {
+            format!("<text x=\"{x}\" y=\"{y}\" font-family=\"sans-serif\" font-size=\"{size}\" \
+                     text-anchor=\"middle\" dominant-baseline=\"central\">{label}</text>\n")
+        }
+
+        // A face of a unit cube in cube_elements.
+        fn face(d: &str, fill: &str) -> String {
+            format!("<path d=\"M{d}Z\" fill=\"{fill}\" \
+                     stroke=\"#000000\" stroke-width=\"1\" stroke-linejoin=\"round\"/>\n")
+        }
+
+        #[test]
+        fn two_copies_of_a_piece() {
+            let board = Board(vec![vec![0, 0, 1, 1, 1],
+                                   vec![0, 0, 0, 1, 1]]);
+            let labels = ["P", "P"].map(String::from);
+            let image = board_svg(&board, |value| labels.get(value).cloned(), &options());
+            let body = [polygon("M1,1 L21,1 L21,11 L31,11 L31,21 L1,21Z", "#4e79a7"),
+                        text(16, 16, 5, "P"),
+                        polygon("M21,1 L51,1 L51,21 L31,21 L31,11 L21,11Z", "#f28e2b"),
+                        text(36, 6, 5, "P")].concat();
+            assert_eq!(image, document(52, 22, &body));
+        }
+
+        #[test]
+        fn holes() {
+            let board = Board(vec![vec![0, 0, 2],
+                                   vec![1, 2, 2]]);
+            let labels = ["I2", "<1>"].map(String::from);
+            let image = board_svg(&board, |value| labels.get(value).cloned(), &options());
+            let body = [polygon("M11,11 L21,11 L21,1 L31,1 L31,21 L11,21Z", "#bab0ac"),
+                        polygon("M1,1 L21,1 L21,11 L1,11Z", "#4e79a7"),
+                        text(6, 6, 5, "I2"),
+                        polygon("M1,11 L11,11 L11,21 L1,21Z", "#f28e2b"),
+                        text(6, 16, 5, "&lt;1&gt;")].concat();
+            assert_eq!(image, document(32, 22, &body));
+            assert!(image.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"32\" height=\"22\" "));
+        }
+
+        #[test]
+        fn cube() {
+            // Two layers of one cell, V on top of T, each with its sides in darker shades of its color.
+            let labels = ["V", "T"].map(String::from);
+            let options = SvgOptions { gap: 10, ..options() };
+            let stack = Cube(vec![vec![vec![0]], vec![vec![1]]]);
+            let image = cube_svg(&stack, |value| labels.get(value).cloned(), &options);
+            let body = [face("18.3,36.0 L9.7,41.0 L9.7,31.0 L18.3,26.0", "#91551a"),
+                        face("1.0,36.0 L9.7,41.0 L9.7,31.0 L1.0,26.0", "#c27222"),
+                        face("9.7,21.0 L18.3,26.0 L9.7,31.0 L1.0,26.0", "#f28e2b"),
+                        text("9.7", "26.0", 3, "T"),
+                        face("18.3,16.0 L9.7,21.0 L9.7,11.0 L18.3,6.0", "#2f4964"),
+                        face("1.0,16.0 L9.7,21.0 L9.7,11.0 L1.0,6.0", "#3e6186"),
+                        face("9.7,1.0 L18.3,6.0 L9.7,11.0 L1.0,6.0", "#4e79a7"),
+                        text("9.7", "6.0", 3, "V")].concat();
+            assert_eq!(image, document(20, 42, &body));
+
+            // A 2x2x2 cube with a hole, which is left out: a label on each of the
+            // other seven cells.
+            let cube = Cube(vec![vec![vec![0, 0], vec![1, 2]],
+                                 vec![vec![0, 1], vec![1, 1]]]);
+            let image = cube_svg(&cube, |value| labels.get(value).cloned(), &options);
+            assert!(image.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"37\" height=\"52\" "));
+            assert_eq!(image.matches(">V</text>").count(), 3);
+            assert_eq!(image.matches(">T</text>").count(), 4);
+            assert_eq!(image.matches("<path ").count(), 15);
+            // The bottom layer comes first, so the top of layer 0 is drawn over it.
+            assert!(image.find(&text("18.3", "36.0", 3, "T")) < image.find(&text("18.3", "6.0", 3, "V")));
+        }
+    }
+}
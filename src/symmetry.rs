@@ -0,0 +1,186 @@
+pub mod symmetry {
+    use std::collections::{HashMap, HashSet};
+
+    // A placement is the list of columns a piece covers in the exact cover
+    // matrix: its cells (below `num_cells`) followed by the columns that tell
+    // which piece it is, the piece kind num_cells + k for piece k.
+
+    // The cells and the other columns of a placement, each sorted.
+    fn split_placement(placement: &[usize], num_cells: usize) -> (Vec<usize>, Vec<usize>) {
+        let (mut cells, mut piece): (Vec<usize>, Vec<usize>) = placement.iter().partition(|&&c| c < num_cells);
+        cells.sort();
+        piece.sort();
+        (cells, piece)
+    }
+
+    /// For every symmetry of the puzzle (a permutation of its cells), the permutation
+    /// it induces on `placements`. A symmetry may trade two pieces, provided it maps
+    /// every placement of one onto a placement of the other and `copies` (indexed by
+    /// piece kind) gives them the same number of copies: the mirror image of a
+    /// one-sided piece is its twin. The symmetries of the cells that do not map the
+    /// placements onto themselves are not symmetries of the puzzle and are left out.
+    pub fn placement_symmetries(placements: &[Vec<usize>],
+                                num_cells: usize,
+                                symmetries: &[Vec<usize>],
+                                copies: &[usize]) -> Vec<Vec<usize>> {
+        let parts: Vec<(Vec<usize>, Vec<usize>)> = placements.iter()
+            .map(|placement| split_placement(placement, num_cells))
+            .collect();
+        let mut index: HashMap<&[usize], Vec<usize>> = HashMap::new();
+        for (r,(cells,_)) in parts.iter().enumerate() {
+            index.entry(cells).or_default().push(r);
+        }
+        let copies_of = |piece: &[usize]| piece.first().and_then(|&c| copies.get(c - num_cells)).copied();
+        symmetries.iter()
+            .filter_map(|permutation| {
+                let mut piece_map: HashMap<&[usize], &[usize]> = HashMap::new();
+                let mut taken: HashSet<&[usize]> = HashSet::new();
+                parts.iter()
+                    .map(|(cells,piece)| {
+                        let mut moved: Vec<usize> = cells.iter().map(|&c| permutation[c]).collect();
+                        moved.sort();
+                        let candidates = index.get(&moved[..])?;
+                        if let Some(&image) = piece_map.get(&piece[..]) {
+                            return candidates.iter().copied().find(|&s| parts[s].1 == image);
+                        }
+                        // The first placement of a piece decides where the piece
+                        // goes, to itself if it can.
+                        let s = candidates.iter()
+                            .copied()
+                            .filter(|&s| !taken.contains(&parts[s].1[..]) && copies_of(&parts[s].1) == copies_of(piece))
+                            .min_by_key(|&s| parts[s].1 != *piece)?;
+                        piece_map.insert(piece, &parts[s].1);
+                        taken.insert(&parts[s].1);
+                        Some(s)
+                    })
+                    .collect::<Option<Vec<usize>>>()
+            })
+            .collect()
+    }
+
+    /// Number of placement symmetries that map the (sorted) answer onto itself.
+    pub fn stabilizer(answer: &[usize], placement_symmetries: &[Vec<usize>]) -> usize {
+        let mut moved = Vec::with_capacity(answer.len());
+        placement_symmetries.iter()
+            .filter(|permutation| {
+                moved.clear();
+                moved.extend(answer.iter().map(|&r| permutation[r]));
+                moved.sort();
+                moved == answer
+            })
+            .count()
+    }
+
+    /// The smallest of the images of the (sorted) answer, the same for all the
+    /// solutions congruent to it.
+    pub fn canonical(answer: &[usize], placement_symmetries: &[Vec<usize>]) -> Vec<usize> {
+        let mut ret = answer.to_vec();
+        let mut moved = Vec::with_capacity(answer.len());
+        for permutation in placement_symmetries {
+            moved.clear();
+            moved.extend(answer.iter().map(|&r| permutation[r]));
+            moved.sort();
+            if moved < ret {
+                ret.clone_from(&moved);
+            }
+        }
+        ret
+    }
+
+    /// Placements left after symmetry breaking.
+    pub struct SymmetryBreak {
+        /// keep[r] tells whether placement r stays in the search.
+        pub keep: Vec<bool>,
+        /// Number of congruent copies of every solution, i.e. the order of the
+        /// symmetry group acting on the placements.
+        pub group_order: usize,
+    }
+
+    /// Picks a piece kind that no non-trivial symmetry maps onto itself in any of its
+    /// placements, nor onto another kind, and keeps a single placement out of each of
+    /// its orbits. The symmetric copies of a solution then put that piece into
+    /// different orbit members, so the search finds every class of congruent
+    /// solutions exactly once. `copies[k]` is the number of pieces of kind k in the
+    /// puzzle, and only kinds used once qualify: the copies of a piece could trade
+    /// orbit members.
+    /// Returns None when the puzzle has no symmetry or no such piece exists.
+    pub fn break_symmetries(kinds: &[usize], placement_symmetries: &[Vec<usize>], copies: &[usize])
+                            -> Option<SymmetryBreak> {
+        let mut nontrivial: Vec<&Vec<usize>> = placement_symmetries.iter()
+            .filter(|permutation| permutation.iter().enumerate().any(|(r,&s)| r != s))
+            .collect();
+        // Different symmetries of a degenerate board (a single row, say) may move
+        // the placements the same way.
+        nontrivial.sort();
+        nontrivial.dedup();
+        if nontrivial.is_empty() {
+            return None;
+        }
+        let mut fixed = vec![false; kinds.iter().max().map_or(0, |&k| k + 1)];
+        let mut num_placements = vec![0; fixed.len()];
+        for (r,&k) in kinds.iter().enumerate() {
+            num_placements[k] += 1;
+            if nontrivial.iter().any(|permutation| permutation[r] == r || kinds[permutation[r]] != k) {
+                fixed[k] = true;
+            }
+        }
+        // The most constrained piece that qualifies, so that Algorithm X picks it early.
+        let kind = (0..fixed.len())
+            .filter(|&k| !fixed[k] && copies.get(k) == Some(&1))
+            .min_by_key(|&k| num_placements[k])?;
+        let mut keep = vec![true; kinds.len()];
+        for (r,&k) in kinds.iter().enumerate() {
+            if k == kind && keep[r] {
+                for permutation in &nontrivial {
+                    keep[permutation[r]] = false;
+                }
+            }
+        }
+        Some(SymmetryBreak { keep, group_order: nontrivial.len() + 1 })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::backtracking::backtracking::count_polyomino_bt;
+        use crate::pieceset::pieceset::PieceSet3d;
+        use crate::solver::solver::*;
+        use crate::testset::testset::test_board;
+
+        // The options for a full search, with symmetry breaking on or off.
+        fn options(symmetry_breaking: bool) -> SolveOptions {
+            SolveOptions { num_solutions: 0, symmetry_breaking, ..SolveOptions::for_size(5) }
+        }
+
+        #[test]
+        fn pentominoes_6x10() {
+            let board = vec![vec![0; 10]; 6];
+            for symmetry_breaking in [true, false] {
+                let count = count_polyomino_dlx(&board, 5, &options(symmetry_breaking)).unwrap();
+                assert_eq!((count.distinct, count.raw), (2339, 9356), "symmetry breaking {}", symmetry_breaking);
+            }
+        }
+
+        #[test]
+        fn pentominoes_8x8_centre_hole() {
+            let board = test_board("501").unwrap();
+            for symmetry_breaking in [true, false] {
+                let options = options(symmetry_breaking);
+                let count = count_polyomino_dlx(&board, 5, &options).unwrap();
+                assert_eq!((count.distinct, count.raw), (65, 520), "symmetry breaking {}", symmetry_breaking);
+                let count = count_polyomino_bt(&board, 5, &options).unwrap();
+                assert_eq!((count.distinct, count.raw), (65, 520), "symmetry breaking {}", symmetry_breaking);
+                assert_eq!(solve_polyomino_dlx_with(&board, 5, &options).unwrap().len(), 65);
+            }
+        }
+
+        #[test]
+        fn soma_cube() {
+            let cube = vec![vec![vec![0; 3]; 3]; 3];
+            for symmetry_breaking in [true, false] {
+                let soma = PieceSet3d::named(&["V", "L", "T", "Z", "A", "B", "P"]).unwrap();
+                let count = count_polycube_dlx(&cube, soma, &options(symmetry_breaking)).unwrap();
+                assert_eq!((count.distinct, count.raw), (240, 11520), "symmetry breaking {}", symmetry_breaking);
+            }
+        }
+    }
+}
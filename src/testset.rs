@@ -1,35 +1,37 @@
 #![allow(dead_code)]
 
 pub mod testset {
-    use crate::pieces::piece::get_num_pieces;
-    use crate::polyominolist::polyominolist::free_polyominos;
+    use crate::pieceset::pieceset::IntoPieceSet;
+    use crate::error::error::SolverError;
     
-    pub fn simple_test_board(n: usize) -> Vec<Vec<usize>> {
-        let num_pieces = get_num_pieces(n);
-        let height: usize = match n {
-            4 => 5, // 5 = 1x5
-            5 => 4, // 12 = 3x4
-            6 => 5, // 35 = 5x7
-            7 => 12, // 108 = 9x12
-            8 => 41, // 369 = 9x41
-            9 => 257, // 1285 = 5x257
-            10 => 95, // 4655 = 49x95
-            11 => 271, // 17073 = 63x271
-            _ => panic!("simple_test_board: not supported number {}", n),
+    /// A board that the pieces are known to fit: every piece in its own square
+    /// of side n, the size of the largest piece, with holes around it. Squares
+    /// left over in the last row are all holes.
+    pub fn simple_test_board(pieces: impl IntoPieceSet) -> Result<Vec<Vec<usize>>, SolverError> {
+        let pieces = pieces.into_piece_set()?;
+        let n = pieces.max_size();
+        let num_pieces = pieces.len();
+        let height: usize = match num_pieces {
+            5 => 5, // 5 = 1x5
+            12 => 4, // 12 = 3x4
+            35 => 5, // 35 = 5x7
+            108 => 12, // 108 = 9x12
+            369 => 41, // 369 = 9x41
+            1285 => 257, // 1285 = 5x257
+            4655 => 95, // 4655 = 49x95
+            17073 => 271, // 17073 = 63x271
+            _ => (1..).find(|&h| h * h >= num_pieces).unwrap_or(1),
         };
-        let width: usize = num_pieces / height;
-        let mut b = vec![vec![0;n*width];n*height];
-        let polyominoes = free_polyominos(n);
-        for (k,p) in polyominoes.iter().enumerate() {
+        let width: usize = num_pieces.div_ceil(height.max(1));
+        let mut b = vec![vec![1;n*width];n*height];
+        for (k,p) in pieces.pieces().iter().enumerate() {
             let i = k / width;
             let j = k % width;
-            for ii in 0..n {
-                for jj in 0..n {
-                    b[i*n + ii][j*n + jj] = if p[ii][jj] == 1 { 0 } else { 1 };
-                }
+            for &(ii,jj) in p {
+                b[i*n + ii][j*n + jj] = 0;
             }
         }
-        b
+        Ok(b)
     }
 
     fn array2vec<const N0: usize, const N1: usize>(arr: [[usize; N1]; N0]) -> Vec<Vec<usize>> {
@@ -49,8 +51,8 @@ pub mod testset {
             .collect::<Vec<Vec<Vec<usize>>>>()
     }
 
-    pub fn test_board(name: &str) -> Vec<Vec<usize>> {
-        match name {
+    pub fn test_board(name: &str) -> Result<Vec<Vec<usize>>, SolverError> {
+        let board = match name {
             "401" => array2vec(BOARD_401),
             "402" => array2vec(BOARD_402),
             "403" => array2vec(BOARD_403),
@@ -73,20 +75,22 @@ pub mod testset {
             "607" => array2vec(BOARD_607),
             "608" => array2vec(BOARD_608),
             "701" => array2vec(BOARD_701),
-            _     => panic!("Unknown name"),
-        }
+            _     => return Err(SolverError::UnknownTestName(name.to_string())),
+        };
+        Ok(board)
     }
 
-    pub fn test_cube(name: &str) -> Vec<Vec<Vec<usize>>> {
-        match name {
+    pub fn test_cube(name: &str) -> Result<Vec<Vec<Vec<usize>>>, SolverError> {
+        let cube = match name {
             "401" => array2vec_3d(CUBE_401),
             "402" => array2vec_3d(CUBE_402),
             "403" => array2vec_3d(CUBE_403),
             "601" => array2vec_3d(CUBE_601),
             "602" => array2vec_3d(CUBE_602),
             "603" => array2vec_3d(CUBE_603),
-            _     => panic!("Unknown name"),
-        }
+            _     => return Err(SolverError::UnknownTestName(name.to_string())),
+        };
+        Ok(cube)
     }
     
     const BOARD_401: [[usize;7];3] = [[0,0,0,0,0,0,0],
@@ -364,13 +368,13 @@ pub mod testset {
                                           [0,0,0,0],
                                           [0,0,0,0],
                                           [0,0,0,0]]];
-    const NUM_SOLUTIONS_C401: usize = 1390;
+    const NUM_SOLUTIONS_C401: usize = 695;
 
     const CUBE_402: [[[usize;8];2];2] = [[[0,0,0,0,0,0,0,0],
                                           [0,0,0,0,0,0,0,0]],
                                          [[0,0,0,0,0,0,0,0],
                                           [0,0,0,0,0,0,0,0]]];
-    const NUM_SOLUTIONS_C402: usize = 224;
+    const NUM_SOLUTIONS_C402: usize = 112;
 
     const CUBE_403: [[[usize;5];4];2] = [[[0,0,1,0,0],
                                           [0,0,1,0,0],
@@ -380,7 +384,7 @@ pub mod testset {
                                           [0,0,1,0,0],
                                           [0,0,1,0,0],
                                           [0,0,1,0,0]]];
-    const NUM_SOLUTIONS_C403: usize = 320;
+    const NUM_SOLUTIONS_C403: usize = 160;
 
     const CUBE_601: [[[usize;25];7];6] = [[[0,0,0,0,0,0,0,0,0,0,0, 0,0,0, 0,0,0,0,0,0,0,0,0,0,0],
                                            [0,0,0,0,0,0,0,0,0,0,0, 0,0,0, 0,0,0,0,0,0,0,0,0,0,0],
@@ -0,0 +1,180 @@
+pub mod validate {
+    use crate::error::error::SolverError;
+
+    // Cheap checks that run before the exact cover matrix or the backtracking
+    // tables are built. Each diagnose_XXX function lists every problem it finds,
+    // and the matching validate_XXX function fails with the first one.
+
+    /// Returns (height, width) of a rectangular, non-empty board.
+    pub fn board_shape(board: &[Vec<usize>]) -> Result<(usize,usize), SolverError> {
+        let width = board.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(SolverError::EmptyBoard);
+        }
+        for (i,row) in board.iter().enumerate() {
+            if row.len() != width {
+                return Err(SolverError::RaggedBoard { index: vec![i], len: row.len(), expected: width });
+            }
+        }
+        Ok((board.len(), width))
+    }
+
+    /// Returns (dim0, dim1, dim2) of a cuboid, non-empty cube.
+    pub fn cube_shape(cube: &[Vec<Vec<usize>>]) -> Result<(usize,usize,usize), SolverError> {
+        let (dim1,dim2) = board_shape(cube.first().ok_or(SolverError::EmptyBoard)?)?;
+        for (i,plane) in cube.iter().enumerate() {
+            if plane.len() != dim1 {
+                return Err(SolverError::RaggedBoard { index: vec![i], len: plane.len(), expected: dim1 });
+            }
+            for (j,row) in plane.iter().enumerate() {
+                if row.len() != dim2 {
+                    return Err(SolverError::RaggedBoard { index: vec![i,j], len: row.len(), expected: dim2 });
+                }
+            }
+        }
+        Ok((cube.len(), dim1, dim2))
+    }
+
+    // Connected components of the open cells of a dim0 x dim1 x dim2 grid,
+    // each one given as a list of flat indices in increasing order.
+    fn open_regions(open: &[bool], dims: [usize;3]) -> Vec<Vec<usize>> {
+        let [dim0,dim1,dim2] = dims;
+        let mut seen = vec![false; open.len()];
+        let mut regions = Vec::new();
+        for start in 0..open.len() {
+            if !open[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut region = Vec::new();
+            let mut stack = vec![start];
+            while let Some(pos) = stack.pop() {
+                region.push(pos);
+                let (i,j,k) = (pos / (dim1*dim2), pos / dim2 % dim1, pos % dim2);
+                let mut neighbors = Vec::with_capacity(6);
+                if i > 0 { neighbors.push(pos - dim1*dim2); }
+                if i + 1 < dim0 { neighbors.push(pos + dim1*dim2); }
+                if j > 0 { neighbors.push(pos - dim2); }
+                if j + 1 < dim1 { neighbors.push(pos + dim2); }
+                if k > 0 { neighbors.push(pos - 1); }
+                if k + 1 < dim2 { neighbors.push(pos + 1); }
+                for next in neighbors {
+                    if open[next] && !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            region.sort();
+            regions.push(region);
+        }
+        regions
+    }
+
+    fn diagnose_cells(open: &[bool],
+                      dims: [usize;3],
+                      piece_area: usize,
+                      n: usize,
+                      index_of: impl Fn(usize) -> Vec<usize>) -> Vec<SolverError> {
+        let mut problems = Vec::new();
+        let open_cells = open.iter().filter(|&&o| o).count();
+        if open_cells != piece_area {
+            problems.push(SolverError::AreaMismatch { open_cells, piece_area });
+        }
+        for region in open_regions(open, dims) {
+            if n > 0 && region.len() % n != 0 {
+                problems.push(SolverError::RegionSize { cell: index_of(region[0]), size: region.len(), n });
+            }
+        }
+        problems
+    }
+
+    /// Lists every reason why `board` cannot be tiled with pieces whose total
+    /// area is `piece_area` and whose sizes are multiples of `n`. Holes are
+    /// non-zero cells.
+    pub fn diagnose_board(board: &[Vec<usize>], piece_area: usize, n: usize) -> Vec<SolverError> {
+        let (height,width) = match board_shape(board) {
+            Ok(shape) => shape,
+            Err(e) => return vec![e],
+        };
+        let open: Vec<bool> = board.iter().flatten().map(|&value| value == 0).collect();
+        diagnose_cells(&open, [1,height,width], piece_area, n, |pos| vec![pos / width, pos % width])
+    }
+
+    /// Same as diagnose_board for a cube.
+    pub fn diagnose_cube(cube: &[Vec<Vec<usize>>], piece_area: usize, n: usize) -> Vec<SolverError> {
+        let (dim0,dim1,dim2) = match cube_shape(cube) {
+            Ok(shape) => shape,
+            Err(e) => return vec![e],
+        };
+        let open: Vec<bool> = cube.iter().flatten().flatten().map(|&value| value == 0).collect();
+        diagnose_cells(&open, [dim0,dim1,dim2], piece_area, n,
+                       |pos| vec![pos / (dim1*dim2), pos / dim2 % dim1, pos % dim2])
+    }
+
+    pub fn validate_board(board: &[Vec<usize>], piece_area: usize, n: usize) -> Result<(usize,usize), SolverError> {
+        match diagnose_board(board, piece_area, n).into_iter().next() {
+            Some(problem) => Err(problem),
+            None => board_shape(board),
+        }
+    }
+
+    pub fn validate_cube(cube: &[Vec<Vec<usize>>], piece_area: usize, n: usize)
+                         -> Result<(usize,usize,usize), SolverError> {
+        match diagnose_cube(cube, piece_area, n).into_iter().next() {
+            Some(problem) => Err(problem),
+            None => cube_shape(cube),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::pieceset::pieceset::PieceSet;
+
+        #[test]
+        fn rejected_boards() {
+            assert_eq!(diagnose_board(&[], 5, 5), vec![SolverError::EmptyBoard]);
+            assert_eq!(diagnose_cube(&[vec![]], 5, 5), vec![SolverError::EmptyBoard]);
+            assert_eq!(diagnose_board(&[vec![0, 0], vec![0]], 3, 3),
+                       vec![SolverError::RaggedBoard { index: vec![1], len: 1, expected: 2 }]);
+            assert_eq!(diagnose_cube(&[vec![vec![0, 0]], vec![vec![0, 0], vec![0]]], 4, 4),
+                       vec![SolverError::RaggedBoard { index: vec![1], len: 2, expected: 1 }]);
+            assert_eq!(diagnose_cube(&[vec![vec![0, 0]], vec![vec![0]]], 3, 3),
+                       vec![SolverError::RaggedBoard { index: vec![1, 0], len: 1, expected: 2 }]);
+            assert_eq!(diagnose_board(&vec![vec![0; 5]; 3], 20, 5),
+                       vec![SolverError::AreaMismatch { open_cells: 15, piece_area: 20 }]);
+            // A column of holes cuts a 2x5 board into two regions of 4 cells.
+            let board = vec![vec![0, 0, 1, 0, 0], vec![0, 0, 1, 0, 0]];
+            assert_eq!(diagnose_board(&board, 8, 5),
+                       vec![SolverError::RegionSize { cell: vec![0, 0], size: 4, n: 5 },
+                            SolverError::RegionSize { cell: vec![0, 3], size: 4, n: 5 }]);
+            assert_eq!(validate_board(&board, 10, 5),
+                       Err(SolverError::AreaMismatch { open_cells: 8, piece_area: 10 }));
+            let cube = vec![vec![vec![0, 1, 0]], vec![vec![0, 1, 0]], vec![vec![1, 1, 0]]];
+            assert_eq!(diagnose_cube(&cube, 5, 5),
+                       vec![SolverError::RegionSize { cell: vec![0, 0, 0], size: 2, n: 5 },
+                            SolverError::RegionSize { cell: vec![0, 0, 2], size: 3, n: 5 }]);
+        }
+
+        #[test]
+        fn mixed_sizes() {
+            // A tetromino and a pentomino: regions of 4 and 5 cells can both be
+            // filled, since the sizes only have 1 in common.
+            let pieces: PieceSet = PieceSet::named(&["I4", "L"]).unwrap();
+            let copies = [1, 1];
+            let (area, unit) = (pieces.area(&copies), pieces.region_unit(&copies));
+            assert_eq!((area, unit), (9, 1));
+            let board = vec![vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 0]];
+            assert_eq!(diagnose_board(&board, area, unit), vec![]);
+            assert_eq!(validate_board(&board, area, unit), Ok((1, 10)));
+
+            // Tetrominoes and hexominoes only fill regions of an even size.
+            let pieces: PieceSet = PieceSet::polyominoes_of_orders(&[4, 6]).unwrap();
+            let unit = pieces.region_unit(&vec![1; pieces.len()]);
+            assert_eq!(unit, 2);
+            assert_eq!(diagnose_board(&[vec![0; 5]], 5, unit),
+                       vec![SolverError::RegionSize { cell: vec![0, 0], size: 5, n: 2 }]);
+        }
+    }
+}